@@ -73,6 +73,14 @@ pub struct UserConfig {
         default = "Option::default"
     )]
     pub open_cmd: Option<String>,
+
+    /// Trust graph traversal costs of this user, instead of the defaults
+    #[serde(
+        rename = "trust-params",
+        skip_serializing_if = "Option::is_none",
+        default = "Option::default"
+    )]
+    pub trust_params: Option<crev_wot::TrustDistanceParams>,
 }
 
 impl Default for UserConfig {
@@ -82,6 +90,7 @@ impl Default for UserConfig {
             current_id: None,
             host_salt: generete_salt(),
             open_cmd: None,
+            trust_params: None,
         }
     }
 }
@@ -95,6 +104,12 @@ impl UserConfig {
     pub fn get_current_userid_opt(&self) -> Option<&Id> {
         self.current_id.as_ref()
     }
+
+    /// `trust-params` from the config, or the defaults if they're not set
+    #[must_use]
+    pub fn trust_params(&self) -> crev_wot::TrustDistanceParams {
+        self.trust_params.clone().unwrap_or_default()
+    }
 }
 
 /// Local config stored in `~/.config/crev`
@@ -117,16 +132,20 @@ impl Local {
             Some(path) => ProjectDirs::from_path(path.into()),
         }
         .ok_or(Error::NoHomeDirectory)?;
+        Ok(Self::from_project_dirs(&proj_dir))
+    }
+
+    fn from_project_dirs(proj_dir: &ProjectDirs) -> Self {
         let config_path = proj_dir.config_dir().into();
         let data_path = proj_dir.data_dir().into();
         let cache_path = proj_dir.cache_dir().into();
-        Ok(Self {
+        Self {
             config_path,
             data_path,
             cache_path,
             cur_url: Mutex::new(None),
             user_config: Mutex::new(None),
-        })
+        }
     }
 
     /// Load all reviews and trust proofs for the current user
@@ -145,6 +164,23 @@ impl Local {
         Ok(db)
     }
 
+    /// Load all `*.crev` proof files found in `path` (recursively), without any crev config or Id.
+    ///
+    /// URLs declared by authors of the proofs are considered verified, as if these were user's own proofs.
+    pub fn load_db_from_dir(path: &Path) -> Result<crev_wot::ProofDB> {
+        if !path.is_dir() {
+            return Err(Error::IO(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("proofs directory {} not found", path.display()),
+            )));
+        }
+        let mut db = crev_wot::ProofDB::new();
+        db.import_from_iter(
+            proofs_iter_for_path(path.to_owned()).map(|p| (p, crev_wot::FetchSource::LocalUser)),
+        );
+        Ok(db)
+    }
+
     /// Where the config is stored
     pub fn config_root(&self) -> &Path {
         &self.config_path
@@ -162,7 +198,18 @@ impl Local {
 
     /// Fails if it doesn't exist. See `auto_create_or_open()`
     pub fn auto_open() -> Result<Self> {
-        let repo = Self::new()?;
+        Self::open(Self::new()?)
+    }
+
+    /// Like `auto_open()`, but in the given directory instead of the default location
+    ///
+    /// Uses the same layout as `CARGO_CREV_ROOT_DIR_OVERRIDE`
+    pub fn open_at(root_dir: &Path) -> Result<Self> {
+        let proj_dir = ProjectDirs::from_path(root_dir.into()).ok_or(Error::NoHomeDirectory)?;
+        Self::open(Self::from_project_dirs(&proj_dir))
+    }
+
+    fn open(repo: Self) -> Result<Self> {
         fs::create_dir_all(repo.cache_remotes_path())?;
         if !repo.config_path.exists() || !repo.user_config_path().exists() {
             return Err(Error::UserConfigNotInitialized);
@@ -1193,8 +1240,12 @@ impl Local {
 
     /// The path must be inside `get_proofs_dir_path()`
     pub fn proof_dir_git_add_path(&self, rel_path: &Path) -> Result<()> {
-        let proof_dir = self.get_proofs_dir_path()?;
-        let repo = git2::Repository::open(proof_dir)?;
+        Self::git_add_path_in_repo_at_path(&self.get_proofs_dir_path()?, rel_path)
+    }
+
+    /// Like `proof_dir_git_add_path`, but for a git checkout anywhere
+    pub fn git_add_path_in_repo_at_path(repo: &Path, rel_path: &Path) -> Result<()> {
+        let repo = git2::Repository::open(repo)?;
         let mut index = repo.index()?;
 
         index.add_path(rel_path)?;
@@ -1204,8 +1255,12 @@ impl Local {
 
     /// Add a commit to user's proof repo
     pub fn proof_dir_commit(&self, commit_msg: &str) -> Result<()> {
-        let proof_dir = self.get_proofs_dir_path()?;
-        let repo = git2::Repository::open(proof_dir)?;
+        Self::git_commit_in_repo_at_path(&self.get_proofs_dir_path()?, commit_msg)
+    }
+
+    /// Like `proof_dir_commit`, but for a git checkout anywhere
+    pub fn git_commit_in_repo_at_path(repo: &Path, commit_msg: &str) -> Result<()> {
+        let repo = git2::Repository::open(repo)?;
         let mut index = repo.index()?;
         let tree_id = index.write_tree()?;
         let tree = repo.find_tree(tree_id)?;
@@ -140,3 +140,41 @@ fn dont_consider_an_empty_review_as_valid() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn trust_params_in_user_config() {
+    let config: local::UserConfig = serde_yaml::from_str(
+        r#"
+version: -1
+current-id: ~
+host-salt: AAAA
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        crev_wot::TrustDistanceParams::default(),
+        config.trust_params()
+    );
+
+    let config: local::UserConfig = serde_yaml::from_str(
+        r#"
+version: -1
+current-id: ~
+host-salt: AAAA
+trust-params:
+  max-distance: 3
+  low-trust-distance: 2
+"#,
+    )
+    .unwrap();
+    let params = config.trust_params();
+    assert_eq!(3, params.max_distance);
+    assert_eq!(2, params.low_trust_distance);
+    assert_eq!(
+        crev_wot::TrustDistanceParams::default().medium_trust_distance,
+        params.medium_trust_distance
+    );
+
+    let yaml = serde_yaml::to_string(&config).unwrap();
+    assert!(yaml.contains("max-distance: 3"));
+}
@@ -1233,6 +1233,9 @@ impl<'a> UrlOfId<'a> {
     }
 }
 
+/// Costs of traversing edges of the trust graph. Can be stored as `trust-params` in crev's user config.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
 pub struct TrustDistanceParams {
     pub max_distance: u64,
     pub high_trust_distance: u64,
@@ -0,0 +1,94 @@
+//! Reading of `audits.toml` files published by others, for merging into our own
+
+use crate::vet;
+use std::collections::BTreeMap;
+
+#[derive(serde::Deserialize)]
+struct AuditsFile {
+    #[serde(default)]
+    audits: BTreeMap<String, Vec<AuditEntry>>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AuditEntry {
+    who: Option<WhoField>,
+    #[serde(default)]
+    criteria: CriteriaField,
+    violation: Option<String>,
+    version: Option<String>,
+    delta: Option<String>,
+    notes: Option<String>,
+    #[serde(default)]
+    aggregated_from: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum WhoField {
+    String(String),
+    Vec(Vec<String>),
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(untagged)]
+enum CriteriaField {
+    #[default]
+    None,
+    String(String),
+    Vec(Vec<String>),
+}
+
+/// Audits from an `audits.toml` downloaded from `url`.
+///
+/// Only `known` criteria are kept, and audits left without any criteria are skipped.
+pub fn parse_audits(
+    url: &str,
+    toml: &str,
+    known: &[&'static str],
+) -> Result<Vec<(String, vet::AuditEntry)>, toml_edit::de::Error> {
+    let file: AuditsFile = toml_edit::de::from_str(toml)?;
+
+    Ok(file
+        .audits
+        .into_iter()
+        .flat_map(|(name, entries)| {
+            entries.into_iter().filter_map(move |e| {
+                let criteria = match e.criteria {
+                    CriteriaField::None => vec![],
+                    CriteriaField::String(c) => vec![c],
+                    CriteriaField::Vec(c) => c,
+                };
+                let mut criteria: Vec<_> = criteria
+                    .iter()
+                    .filter_map(|c| known.iter().find(|k| **k == c).copied())
+                    .collect();
+                // cargo-vet would reject the whole file over an audit without an author
+                if criteria.is_empty() || (e.violation.is_none() && e.who.is_none()) {
+                    return None;
+                }
+                criteria.sort_unstable();
+                let mut aggregated_from = e.aggregated_from;
+                if aggregated_from.is_empty() {
+                    aggregated_from.push(url.to_string());
+                }
+                Some((
+                    name.clone(),
+                    vet::AuditEntry {
+                        who: match e.who {
+                            Some(WhoField::String(who)) => vet::StringOrVec::String(who),
+                            Some(WhoField::Vec(who)) => vet::StringOrVec::Vec(who),
+                            None => vet::StringOrVec::Vec(vec![]),
+                        },
+                        violation: e.violation,
+                        criteria,
+                        version: e.version,
+                        delta: e.delta,
+                        notes: e.notes,
+                        aggregated_from,
+                    },
+                ))
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,132 @@
+//! Minimal reader of Alpine's `aports` tree
+//!
+//! Alpine doesn't package crates individually. Rust applications vendor their dependencies,
+//! and some APKBUILDs list the `.crate` files they download in `source=`, so a single
+//! APKBUILD can vouch for many crates.
+
+use crate::importer;
+use crate::Error;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const APORTS_REPO_URL: &str = "https://gitlab.alpinelinux.org/alpine/aports";
+
+/// Repositories of the aports tree that are shipped in releases
+const REPOS: [&str; 3] = ["main", "community", "testing"];
+
+pub struct Crate {
+    pub name: String,
+    pub version: String,
+    /// `community/foo`
+    pub origin: String,
+}
+
+pub fn aports_dir(temp_dir_path: &Path) -> PathBuf {
+    temp_dir_path.join("aports")
+}
+
+/// Clones or updates a shallow checkout of aports in `temp_dir_path`
+pub fn update_aports(temp_dir_path: &Path) -> Result<PathBuf, Error> {
+    let checkout = aports_dir(temp_dir_path);
+    importer::update_shallow_checkout(APORTS_REPO_URL, &checkout)?;
+    Ok(checkout)
+}
+
+/// All crates downloaded by APKBUILDs in the checkout
+pub fn list_all(aports_dir: &Path) -> io::Result<Vec<Crate>> {
+    let mut out = Vec::new();
+    for repo in REPOS {
+        let repo_dir = aports_dir.join(repo);
+        if !repo_dir.exists() {
+            continue;
+        }
+        for pkg_dir in std::fs::read_dir(&repo_dir)? {
+            let pkg_dir = pkg_dir?;
+            let apkbuild = match std::fs::read_to_string(pkg_dir.path().join("APKBUILD")) {
+                Ok(a) => a,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            let origin = format!("{repo}/{}", pkg_dir.file_name().to_string_lossy());
+            for (name, version) in crates_in_apkbuild(&apkbuild) {
+                out.push(Crate {
+                    name,
+                    version,
+                    origin: origin.clone(),
+                });
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Finds crates.io downloads in the `source=` of an APKBUILD
+fn crates_in_apkbuild(apkbuild: &str) -> Vec<(String, String)> {
+    let vars = variables(apkbuild);
+    let Some(source) = vars.get("source") else {
+        return Vec::new();
+    };
+    source
+        .split_whitespace()
+        .filter_map(|src| {
+            // `local-name::url` renames the downloaded file
+            let url = src.rsplit_once("::").map_or(src, |(_, url)| url);
+            importer::crate_from_url(&expand(url, &vars))
+        })
+        .collect()
+}
+
+/// Top-level `var=value` assignments. APKBUILDs are shell scripts, but the metadata is kept simple.
+fn variables(apkbuild: &str) -> HashMap<&str, String> {
+    let mut vars = HashMap::new();
+    let mut lines = apkbuild.lines();
+    while let Some(line) = lines.next() {
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        if name.is_empty() || !name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            continue;
+        }
+        let mut value = value.to_string();
+        if let Some(quote) = value.chars().next().filter(|&c| c == '"' || c == '\'') {
+            value.remove(0);
+            // multi-line values are common in `source=`
+            while !value.contains(quote) {
+                let Some(next) = lines.next() else { break };
+                value.push('\n');
+                value.push_str(next);
+            }
+            if let Some(end) = value.find(quote) {
+                value.truncate(end);
+            }
+        }
+        vars.insert(name, value);
+    }
+    vars
+}
+
+/// Substitutes `$var` and `${var}` with values known from the APKBUILD
+fn expand(s: &str, vars: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let (name, after) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.split_once('}') {
+                Some(split) => split,
+                None => (braced, ""),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            rest.split_at(end)
+        };
+        out.push_str(vars.get(name).map(|v| v.as_str()).unwrap_or_default());
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
@@ -1,5 +1,6 @@
-use crevette::Crevette;
+use crevette::vet::VetSchema;
 use crevette::Error;
+use crevette::{Crevette, UrlStatus};
 use std::error::Error as _;
 use std::process::ExitCode;
 
@@ -18,46 +19,207 @@ fn main() -> ExitCode {
 }
 
 fn run() -> Result<(), Error> {
+    let mut vet_schema = VetSchema::default();
     match std::env::args().nth(1).as_deref() {
         Some("--help") => {
             eprintln!("https://lib.rs/crevette {}
 Run without args to update your crev repo.
-Run with --debcargo to make a vet file from Debian package list.", env!("CARGO_PKG_VERSION"));
-            return Ok(())
-        },
+Run with --format=vet-v1 to update it with a file for old cargo-vet versions that don't support `aggregated-from`.
+Run with --debcargo to make a vet file from Debian package list.
+Add --suite=<name> to use a Debian suite other than stable, e.g. --suite=sid.
+Add --mirror=<url> to download from a Debian mirror other than deb.debian.org.
+Add --max-cache-age=<days> to download the Debian or openSUSE package list again in full if the cached one is older.
+Run with --guix, --alpine, --homebrew or --opensuse to make a vet file from Guix, Alpine, Homebrew or openSUSE package lists.
+Add --category=<name> to --guix to use only packages in that Guix category, e.g. --category=rust. It can be repeated.
+Run with --conda <feedstock>... to make a vet file from recipes of conda-forge feedstocks, e.g. --conda ripgrep.
+Run with --sbom <file.json> to make a vet file from a CycloneDX SBOM. Add --spdx if it's an SPDX SBOM.
+Add --offline to use only previously downloaded Debian, Guix, Alpine, Homebrew, openSUSE or conda-forge package lists.", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
         Some("--debcargo") => {
             if !cfg!(feature = "debcargo") {
-                eprintln!("Reinstall with debcargo enabled:\ncargo install crevette --features=debcargo");
-                return Err(Error::UnsupportedVersion(0));
+                eprintln!(
+                    "Reinstall with debcargo enabled:\ncargo install crevette --features=debcargo"
+                );
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
             }
             #[cfg(feature = "debcargo")]
             {
                 let dirs = directories_next::BaseDirs::new().unwrap();
                 let cache_dir = dirs.cache_dir().join("crevette");
-                println!("{}", Crevette::from_debcargo_repo(&cache_dir)?);
-                return Ok(())
+                let mut opts = crevette::DebianOptions {
+                    offline: std::env::args().skip(2).any(|a| a == "--offline"),
+                    mirror: std::env::args()
+                        .skip(2)
+                        .find_map(|a| a.strip_prefix("--mirror=").map(String::from)),
+                    ..Default::default()
+                };
+                if let Some(suite) = std::env::args()
+                    .skip(2)
+                    .find_map(|a| a.strip_prefix("--suite=").map(String::from))
+                {
+                    opts.suite = suite;
+                }
+                opts.max_cache_age = max_cache_age_arg();
+                println!("{}", Crevette::from_debcargo_repo(&cache_dir, &opts, None)?);
+                return Ok(());
             }
-        },
+        }
         Some("--guix") => {
             if !cfg!(feature = "guix") {
                 eprintln!("Reinstall with guix enabled:\ncargo install crevette --features=guix");
-                return Err(Error::UnsupportedVersion(0));
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
             }
             #[cfg(feature = "guix")]
             {
                 let dirs = directories_next::BaseDirs::new().unwrap();
                 let cache_dir = dirs.cache_dir().join("crevette");
-                println!("{}", Crevette::from_guix_repo(&cache_dir)?);
-                return Ok(())
+                let categories: Vec<_> = std::env::args()
+                    .skip(2)
+                    .filter_map(|a| a.strip_prefix("--category=").map(String::from))
+                    .collect();
+                let categories: Vec<_> = categories.iter().map(String::as_str).collect();
+                let offline = std::env::args().skip(2).any(|a| a == "--offline");
+                println!(
+                    "{}",
+                    Crevette::from_guix_repo(
+                        &cache_dir,
+                        (!categories.is_empty()).then_some(&categories[..]),
+                        offline,
+                        None
+                    )?
+                );
+                return Ok(());
+            }
+        }
+        Some("--alpine") => {
+            if !cfg!(feature = "alpine") {
+                eprintln!(
+                    "Reinstall with alpine enabled:\ncargo install crevette --features=alpine"
+                );
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
+            }
+            #[cfg(feature = "alpine")]
+            {
+                let dirs = directories_next::BaseDirs::new().unwrap();
+                let cache_dir = dirs.cache_dir().join("crevette");
+                let offline = std::env::args().skip(2).any(|a| a == "--offline");
+                println!("{}", Crevette::from_alpine_repo(&cache_dir, offline, None)?);
+                return Ok(());
+            }
+        }
+        Some("--homebrew") => {
+            if !cfg!(feature = "homebrew") {
+                eprintln!(
+                    "Reinstall with homebrew enabled:\ncargo install crevette --features=homebrew"
+                );
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
             }
-        },
+            #[cfg(feature = "homebrew")]
+            {
+                let dirs = directories_next::BaseDirs::new().unwrap();
+                let cache_dir = dirs.cache_dir().join("crevette");
+                let offline = std::env::args().skip(2).any(|a| a == "--offline");
+                println!(
+                    "{}",
+                    Crevette::from_homebrew_repo(&cache_dir, offline, None)?
+                );
+                return Ok(());
+            }
+        }
+        Some("--opensuse") => {
+            if !cfg!(feature = "opensuse") {
+                eprintln!(
+                    "Reinstall with opensuse enabled:\ncargo install crevette --features=opensuse"
+                );
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
+            }
+            #[cfg(feature = "opensuse")]
+            {
+                let dirs = directories_next::BaseDirs::new().unwrap();
+                let cache_dir = dirs.cache_dir().join("crevette");
+                let opts = crevette::OpensuseOptions {
+                    offline: std::env::args().skip(2).any(|a| a == "--offline"),
+                    max_cache_age: max_cache_age_arg(),
+                    ..Default::default()
+                };
+                println!("{}", Crevette::from_opensuse_repo(&cache_dir, &opts, None)?);
+                return Ok(());
+            }
+        }
+        Some("--conda") => {
+            if !cfg!(feature = "conda") {
+                eprintln!("Reinstall with conda enabled:\ncargo install crevette --features=conda");
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
+            }
+            #[cfg(feature = "conda")]
+            {
+                let dirs = directories_next::BaseDirs::new().unwrap();
+                let cache_dir = dirs.cache_dir().join("crevette");
+                let offline = std::env::args().skip(2).any(|a| a == "--offline");
+                let feedstocks: Vec<_> = std::env::args()
+                    .skip(2)
+                    .filter(|a| !a.starts_with("--"))
+                    .collect();
+                if feedstocks.is_empty() {
+                    eprintln!("Specify names of conda-forge feedstocks: --conda <name>...");
+                    return Err(crev_lib::Error::UnsupportedVersion(0).into());
+                }
+                let feedstocks: Vec<_> = feedstocks.iter().map(String::as_str).collect();
+                println!(
+                    "{}",
+                    Crevette::from_condaforge_repo(&cache_dir, &feedstocks, offline, None)?
+                );
+                return Ok(());
+            }
+        }
+        Some("--sbom") => {
+            if !cfg!(feature = "sbom") {
+                eprintln!("Reinstall with sbom enabled:\ncargo install crevette --features=sbom");
+                return Err(crev_lib::Error::UnsupportedVersion(0).into());
+            }
+            #[cfg(feature = "sbom")]
+            {
+                let Some(path) = std::env::args().skip(2).find(|a| !a.starts_with("--")) else {
+                    eprintln!("Specify path to the SBOM file: --sbom <file.json>");
+                    return Err(crev_lib::Error::UnsupportedVersion(0).into());
+                };
+                let format = if std::env::args().skip(2).any(|a| a == "--spdx") {
+                    crevette::SbomFormat::Spdx
+                } else {
+                    crevette::SbomFormat::CycloneDx
+                };
+                println!("{}", Crevette::from_sbom(path.as_ref(), format)?);
+                return Ok(());
+            }
+        }
+        Some(format) if format.starts_with("--format=") => {
+            vet_schema = match &format["--format=".len()..] {
+                "vet-v1" => VetSchema::V1,
+                "vet-v2" => VetSchema::V2,
+                other => {
+                    eprintln!("unknown format: {other} (expected vet-v1 or vet-v2)");
+                    return Err(crev_lib::Error::UnsupportedVersion(0).into());
+                }
+            };
+        }
         Some(other) => {
             eprintln!("unknown argument: {other}");
-        },
-        None => {},
+        }
+        None => {}
     }
-    let res = Crevette::new().and_then(|c| c.convert_into_repo())?;
-        println!(
+    let mut crevette = Crevette::new()?;
+    crevette.set_vet_schema(vet_schema);
+    for (url, ids) in crevette.shared_reviewer_urls() {
+        eprintln!("warning: {} crev Ids have the same URL {url} (rotated keys?). Their audits are told apart by the Id.", ids.len());
+    }
+    let res = crevette.convert_into_repo()?;
+    match res.url_status {
+        UrlStatus::Supported => {},
+        UrlStatus::NoRemote => eprintln!("The crev proofs repo has no `origin` remote. Set it with `cargo crev repo git remote add origin <url>`"),
+        UrlStatus::Unsupported => eprintln!("Can't tell the public URL of audits.toml for {} (only GitHub and GitLab are supported)", res.repo_git_url.as_deref().unwrap_or_default()),
+    }
+    println!(
             "Wrote '{}'\nRun `cargo crev publish` to upload the file to {}\nThen run `cargo vet import yourname {}`\n",
             res.local_path.display(),
             res.repo_git_url.as_deref().unwrap_or("your git repo (not configured yet?)"),
@@ -65,3 +227,12 @@ Run with --debcargo to make a vet file from Debian package list.", env!("CARGO_P
         );
     Ok(())
 }
+
+/// `--max-cache-age=<days>`
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+fn max_cache_age_arg() -> Option<std::time::Duration> {
+    std::env::args()
+        .skip(2)
+        .find_map(|a| a.strip_prefix("--max-cache-age=")?.parse::<u64>().ok())
+        .map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60))
+}
@@ -0,0 +1,116 @@
+//! Minimal reader of conda-forge feedstocks
+//!
+//! Each package has its own `*-feedstock` repo, and there's no list of the ones built with Rust, so feedstocks are given by name.
+//! Only recipes that download their source from crates.io vouch for a crate. Sources from git or GitHub tarballs have no crates.io version.
+
+use crate::importer;
+use crate::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const CONDA_FORGE_GITHUB_URL: &str = "https://github.com/conda-forge";
+
+pub struct Crate {
+    pub name: String,
+    pub version: String,
+    pub feedstock: String,
+}
+
+pub fn feedstock_url(feedstock: &str) -> String {
+    format!("{CONDA_FORGE_GITHUB_URL}/{feedstock}-feedstock")
+}
+
+pub fn feedstock_dir(temp_dir_path: &Path, feedstock: &str) -> PathBuf {
+    temp_dir_path
+        .join("conda-forge")
+        .join(format!("{feedstock}-feedstock"))
+}
+
+/// Clones or updates a shallow checkout of the feedstock in `temp_dir_path`
+pub fn update_feedstock(temp_dir_path: &Path, feedstock: &str) -> Result<PathBuf, Error> {
+    let checkout = feedstock_dir(temp_dir_path, feedstock);
+    importer::update_shallow_checkout(&feedstock_url(feedstock), &checkout)?;
+    Ok(checkout)
+}
+
+/// Crates that recipes of the given feedstocks, checked out in `temp_dir_path`, download from crates.io
+pub fn list_all(temp_dir_path: &Path, feedstocks: &[&str]) -> io::Result<Vec<Crate>> {
+    let mut out = Vec::new();
+    for &feedstock in feedstocks {
+        let recipe_dir = feedstock_dir(temp_dir_path, feedstock).join("recipe");
+        // recipe.yaml is the newer recipe format
+        let recipe = match std::fs::read_to_string(recipe_dir.join("meta.yaml")) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                std::fs::read_to_string(recipe_dir.join("recipe.yaml"))?
+            }
+            res => res?,
+        };
+        for (name, version) in crates_in_recipe(&recipe) {
+            out.push(Crate {
+                name,
+                version,
+                feedstock: feedstock.into(),
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// crates.io downloads in `url:` fields of the recipe, after expanding its variables
+pub fn crates_in_recipe(recipe: &str) -> Vec<(String, String)> {
+    let vars = recipe_variables(recipe);
+    recipe
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start().trim_start_matches("- ");
+            let url = line
+                .strip_prefix("url:")?
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'');
+            let mut url = url.to_string();
+            for (name, value) in &vars {
+                for pattern in [
+                    format!("${{{{ {name} }}}}"),
+                    format!("{{{{ {name} }}}}"),
+                    format!("{{{{{name}}}}}"),
+                ] {
+                    url = url.replace(&pattern, value);
+                }
+            }
+            importer::crate_from_url(&url)
+        })
+        .collect()
+}
+
+/// `{% set name = "value" %}` of `meta.yaml`, and top-level `context:` entries of `recipe.yaml`
+fn recipe_variables(recipe: &str) -> Vec<(String, String)> {
+    let unquote = |v: &str| v.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+    let mut vars = Vec::new();
+    let mut in_context = false;
+    for line in recipe.lines() {
+        if let Some(set) = line
+            .trim()
+            .strip_prefix("{%")
+            .and_then(|l| l.strip_suffix("%}"))
+        {
+            if let Some((name, value)) = set
+                .trim()
+                .strip_prefix("set ")
+                .and_then(|s| s.split_once('='))
+            {
+                vars.push((name.trim().to_string(), unquote(value)));
+            }
+            continue;
+        }
+        if !line.starts_with(' ') {
+            in_context = line.trim_end() == "context:";
+            continue;
+        }
+        if in_context {
+            if let Some((name, value)) = line.split_once(':') {
+                vars.push((name.trim().to_string(), unquote(value)));
+            }
+        }
+    }
+    vars
+}
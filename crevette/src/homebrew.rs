@@ -0,0 +1,73 @@
+//! Minimal reader of Homebrew's `homebrew-core` tap
+//!
+//! Formulae of Rust programs may download crates as `resource`s, so one formula can vouch for many crates.
+
+use crate::importer;
+use crate::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const HOMEBREW_CORE_REPO_URL: &str = "https://github.com/Homebrew/homebrew-core";
+
+pub struct Crate {
+    pub name: String,
+    pub version: String,
+    pub formula: String,
+}
+
+pub fn homebrew_core_dir(temp_dir_path: &Path) -> PathBuf {
+    temp_dir_path.join("homebrew-core")
+}
+
+/// Clones or updates a shallow checkout of homebrew-core in `temp_dir_path`
+pub fn update_homebrew_core(temp_dir_path: &Path) -> Result<PathBuf, Error> {
+    let checkout = homebrew_core_dir(temp_dir_path);
+    importer::update_shallow_checkout(HOMEBREW_CORE_REPO_URL, &checkout)?;
+    Ok(checkout)
+}
+
+/// All crates downloaded by formulae in the checkout
+pub fn list_all(homebrew_core_dir: &Path) -> io::Result<Vec<Crate>> {
+    let mut out = Vec::new();
+    let mut dirs = vec![homebrew_core_dir.join("Formula")];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path.extension().map_or(true, |ext| ext != "rb") {
+                continue;
+            }
+            let Some(formula) = path.file_stem().map(|f| f.to_string_lossy().into_owned()) else {
+                continue;
+            };
+            let source = std::fs::read_to_string(&path)?;
+            for (name, version) in crates_in_formula(&source) {
+                out.push(Crate {
+                    name,
+                    version,
+                    formula: formula.clone(),
+                });
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// crates.io downloads in `url "…"` lines, both of the formula itself and its `resource` blocks
+fn crates_in_formula(source: &str) -> Vec<(String, String)> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let url = line
+                .trim_start()
+                .strip_prefix("url ")?
+                .trim_start()
+                .strip_prefix('"')?;
+            let (url, _) = url.split_once('"')?;
+            importer::crate_from_url(url)
+        })
+        .collect()
+}
@@ -0,0 +1,55 @@
+//! Helpers shared by importers of package repositories
+
+use crate::Error;
+use std::path::Path;
+
+/// Clones only the latest commit of `repo_url` into `checkout`, or updates it to the latest commit if it's already there
+pub fn update_shallow_checkout(repo_url: &str, checkout: &Path) -> Result<(), Error> {
+    let fetch_err = |e: git2::Error| Error::GitFetch(Box::new((repo_url.into(), e)));
+
+    let mut fetch_options = crev_lib::util::git::default_fetch_options();
+    fetch_options.depth(1);
+    if !checkout.join(".git").exists() {
+        git2::build::RepoBuilder::new()
+            .fetch_options(fetch_options)
+            .clone(repo_url, checkout)
+            .map_err(fetch_err)?;
+        return Ok(());
+    }
+
+    let mut update = || {
+        let repo = git2::Repository::open(checkout)?;
+        repo.find_remote("origin")?
+            .fetch(&["HEAD"], Some(&mut fetch_options), None)?;
+        let latest = repo.find_reference("FETCH_HEAD")?.peel_to_commit()?;
+        repo.checkout_tree(
+            latest.as_object(),
+            Some(git2::build::CheckoutBuilder::new().force()),
+        )?;
+        repo.set_head_detached(latest.id())
+    };
+    update().map_err(fetch_err)
+}
+
+/// Name and version of a crate from its crates.io download URL
+pub fn crate_from_url(url: &str) -> Option<(String, String)> {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    if let Some(rest) = url.strip_prefix("crates.io/api/v1/crates/") {
+        let mut parts = rest.split('/');
+        let name = parts.next()?;
+        let version = parts.next()?;
+        if parts.next() != Some("download") {
+            return None;
+        }
+        return Some((name.into(), version.into()));
+    }
+    let rest = url.strip_prefix("static.crates.io/crates/")?;
+    let (name, file) = rest.split_once('/')?;
+    let version = file
+        .strip_suffix(".crate")?
+        .strip_prefix(name)?
+        .strip_prefix('-')?;
+    Some((name.into(), version.into()))
+}
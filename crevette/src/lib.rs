@@ -1,25 +1,295 @@
-use crev_data::proof::PackageInfo;
-use crev_data::review::Package;
+use crev_data::proof::{Date, Digest, PackageId, PackageInfo};
+use crev_data::review::{Flags, Package};
 use crev_data::Review;
 use crev_data::{Id, Level, PublicId, Rating, TrustLevel, Url, SOURCE_CRATES_IO};
 use crev_lib::Local;
 use crev_wot::ProofDB;
 use crev_wot::TrustSet;
 use crev_wot::{PkgVersionReviewId, TrustDistanceParams};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+#[cfg(feature = "aggregate")]
+mod aggregate;
+#[cfg(feature = "alpine")]
+mod alpine;
+#[cfg(feature = "conda")]
+mod conda;
+#[cfg(feature = "homebrew")]
+mod homebrew;
+#[cfg(any(feature = "alpine", feature = "homebrew", feature = "conda"))]
+mod importer;
+#[cfg(feature = "opensuse")]
+mod opensuse;
+#[cfg(feature = "sbom")]
+mod sbom;
 pub mod vet;
 
-pub use crev_lib::Error;
+/// Failures of crevette, or of crev it uses
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// See [`crev_lib::Error`]
+    #[error(transparent)]
+    Crev(#[from] crev_lib::Error),
 
+    /// Data couldn't be written as TOML. This is a bug, not an IO problem.
+    #[error("Can't serialize TOML: {0}")]
+    TomlSerialization(String),
+
+    /// Data couldn't be written as JSON. This is a bug, not an IO problem.
+    #[error("Can't serialize JSON: {0}")]
+    JsonSerialization(String),
+
+    /// A file or a download isn't in the expected format. Has the source and the reason.
+    #[error("Can't parse {}: {}", _0.0, _0.1)]
+    Parse(Box<(String, String)>),
+
+    /// An importer couldn't read the package list it has downloaded to the given directory
+    #[error("Error reading package index at {}: {}", _0.0.display(), _0.1)]
+    PackageIndex(Box<(PathBuf, String)>),
+
+    /// The audits break cargo-vet's rules, so it would reject the file
+    #[error(transparent)]
+    Invalid(#[from] vet::InvalidAudit),
+
+    /// Exporting reviews would produce an empty file, usually because of too strict filters
+    #[error("No reviews matched the filters, so there is nothing to export")]
+    NoReviewsExported,
+
+    /// In strict mode, reviews qualified for an audit, but had to be dropped. Has the reasons.
+    #[error("{} qualifying reviews were dropped: {}", _0.len(), _0.join("; "))]
+    QualifiedReviewsDropped(Vec<String>),
+
+    /// An importer couldn't clone or update the git repository at the given URL
+    #[error("Can't fetch {}: {}", _0.0, _0.1)]
+    GitFetch(Box<(String, git2::Error)>),
+
+    /// Misc problems with file I/O and downloads
+    #[error("I/O: {}", _0)]
+    IO(#[from] io::Error),
+
+    /// Misc problems with file I/O
+    #[error("Error writing to {}: {}", _1.display(), _0)]
+    FileWrite(io::Error, PathBuf),
+}
+
+/// Clones share the db and the trust set, so they're cheap
+#[derive(Clone)]
 pub struct Crevette {
-    db: ProofDB,
-    trusts: TrustSet,
-    min_trust_level: TrustLevel,
+    db: Arc<ProofDB>,
+    trusts: Arc<TrustSet>,
+    /// Verified URL -> trusted Ids that share it
+    shared_urls: Arc<BTreeMap<String, Vec<Id>>>,
+    opts: ConvertOptions,
+    /// `None` for crev's default location
+    crev_root_dir: Option<PathBuf>,
+    include_header: bool,
+    header_override: Option<String>,
+    allow_empty: bool,
+    emit_checksum: bool,
+    progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+}
+
+/// How many crates or packages have been processed so far, out of `total`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Summary of a conversion for CI, returned by `convert_with_report`. Serializable as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ConversionReport {
+    pub entry_count: usize,
+    /// Crate name -> number of its audits and violations
+    pub entries_per_crate: BTreeMap<String, usize>,
+    /// `who` of all entries, sorted, without duplicates
+    pub reviewers: BTreeSet<String>,
+    /// Reviews that qualified for an audit, but had to be dropped, with the reason
+    pub dropped_reviews: Vec<String>,
+    /// Hex-encoded SHA-256 of the TOML
+    pub sha256: String,
+}
+
+/// Settings of the conversion of reviews into audits, changed only by `Crevette`'s setters.
+///
+/// Get them from `Crevette::options` (or use the defaults) to pass them to `build_audits`.
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    pub(crate) min_trust_level: TrustLevel,
+    pub(crate) trust_criteria_names: [&'static str; 3],
     /// Presenve of a git rev makes vargo-vet ignore the review entirely
-    include_git_revs: bool,
+    pub(crate) include_git_revs: bool,
+    pub(crate) who_format: WhoFormat,
+    pub(crate) unmaintained_as_violation: bool,
+    pub(crate) include_provenance: bool,
+    pub(crate) scoring_policy: ScoringPolicy,
+    /// crate name -> number of crates depending on it
+    pub(crate) reverse_dependencies: Option<HashMap<String, u64>>,
+    /// Registry source of exported reviews, instead of `SOURCE_CRATES_IO`
+    pub(crate) source_override: Option<String>,
+    pub(crate) max_note_len: Option<usize>,
+    /// `(crate name, audit)` from other people's `audits.toml`
+    pub(crate) aggregated: Vec<(String, vet::AuditEntry)>,
+    pub(crate) max_entries_per_crate: Option<usize>,
+    pub(crate) annotate_scores: bool,
+    pub(crate) emit_all_reviews: bool,
+    pub(crate) violation_criteria: Option<BTreeMap<Level, Vec<&'static str>>>,
+    pub(crate) custom_criteria: BTreeMap<&'static str, vet::CriteriaEntry>,
+    pub(crate) include_source_url: bool,
+    pub(crate) note_format: NoteFormat,
+    /// `(crate name, version)`
+    pub(crate) yanked: HashSet<(String, semver::Version)>,
+    pub(crate) skip_yanked: bool,
+    pub(crate) include_criteria_block: bool,
+    pub(crate) audit_portal_url_template: String,
+    /// Sources of crates that are reviewed from git, and have no registry versions
+    pub(crate) git_sources: Vec<String>,
+    /// `None` allows all criteria
+    pub(crate) allowed_criteria: Option<HashSet<String>>,
+    /// Language tags, like `de`, of reviewers' comments
+    pub(crate) reviewer_languages: HashMap<Id, String>,
+    /// crate name -> minimum trust level, instead of `min_trust_level`
+    pub(crate) crate_trust_overrides: HashMap<String, TrustLevel>,
+    pub(crate) include_neutral_reviews: bool,
+    /// Id the audits are exported for, which is the root of the trust set
+    pub(crate) exporting_id: Option<Id>,
+    /// Skip reviews by `exporting_id`
+    pub(crate) exclude_self_reviews: bool,
+    /// Explain in criteria descriptions how they're derived from crev reviews
+    pub(crate) verbose_criteria_descriptions: bool,
+    pub(crate) vet_schema: vet::VetSchema,
+    /// Fail the conversion if reviews that qualified for an audit had to be dropped
+    pub(crate) strict: bool,
+    /// Suggest `[policy]` entries in `convert_to_config_document`
+    pub(crate) suggest_policy: bool,
+    /// Write `who` of crev audits as an array, like importers do, even when there's one reviewer
+    pub(crate) who_always_vec: bool,
+    /// Export only crates with names matching any of these patterns, with `*` and `?` wildcards. Empty exports all.
+    pub(crate) crate_globs: Vec<String>,
+    /// Start notes of violations with counts of advisories per severity
+    pub(crate) severity_summary: bool,
+    /// Name severities like advisory databases do ("critical", "moderate") rather than crev's levels ("high", "medium")
+    pub(crate) advisory_severity_names: bool,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            min_trust_level: TrustLevel::Low,
+            trust_criteria_names: DEFAULT_TRUST_CRITERIA_NAMES,
+            include_git_revs: false,
+            who_format: WhoFormat::default(),
+            unmaintained_as_violation: false,
+            include_provenance: true,
+            scoring_policy: ScoringPolicy::default(),
+            reverse_dependencies: None,
+            source_override: None,
+            max_note_len: None,
+            aggregated: Vec::new(),
+            max_entries_per_crate: None,
+            annotate_scores: false,
+            emit_all_reviews: false,
+            violation_criteria: None,
+            custom_criteria: BTreeMap::new(),
+            include_source_url: false,
+            note_format: NoteFormat::default(),
+            yanked: HashSet::new(),
+            skip_yanked: false,
+            include_criteria_block: true,
+            audit_portal_url_template: DEFAULT_AUDIT_PORTAL_URL_TEMPLATE.into(),
+            git_sources: Vec::new(),
+            allowed_criteria: None,
+            reviewer_languages: HashMap::new(),
+            crate_trust_overrides: HashMap::new(),
+            include_neutral_reviews: true,
+            exporting_id: None,
+            exclude_self_reviews: false,
+            verbose_criteria_descriptions: false,
+            vet_schema: vet::VetSchema::default(),
+            strict: false,
+            suggest_policy: false,
+            who_always_vec: false,
+            crate_globs: Vec::new(),
+            severity_summary: false,
+            advisory_severity_names: false,
+        }
+    }
+}
+
+/// Same audits as `Crevette::convert_to_document`, from any db and trust set, e.g. for testing scoring on synthetic proofs
+pub fn build_audits(
+    db: &ProofDB,
+    trusts: &TrustSet,
+    opts: &ConvertOptions,
+) -> Result<vet::AuditsFile, Error> {
+    let shared_urls = shared_verified_urls(db, trusts);
+    Converter {
+        db,
+        trusts,
+        shared_urls: &shared_urls,
+        opts,
+        progress: None,
+        dropped: None,
+        without_details: false,
+    }
+    .convert_to_document()
+}
+
+/// The conversion, borrowing everything it needs
+#[derive(Clone, Copy)]
+struct Converter<'a> {
+    db: &'a ProofDB,
+    trusts: &'a TrustSet,
+    shared_urls: &'a BTreeMap<String, Vec<Id>>,
+    opts: &'a ConvertOptions,
+    progress: Option<&'a (dyn Fn(Progress) + Sync)>,
+    /// Reviews dropped despite qualifying, collected in strict mode
+    dropped: Option<&'a std::cell::RefCell<Vec<String>>>,
+    /// Leaves out notes and provenance, when only the entries' count matters
+    without_details: bool,
+}
+
+/// Layout of notes of audits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteFormat {
+    /// The comment, followed by `severity:` and `id:` lines of every advisory
+    #[default]
+    Plain,
+    /// Sections for the comment, a list of advisories with linked ids, and other details
+    Markdown,
+}
+
+/// Format of the JSON SBOM read by `from_sbom`
+#[cfg(feature = "sbom")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+/// Everything needed to export reviews from any crev config, from perspective of any Id in it
+pub struct CrevetteConfig {
+    /// crev's own directory, like `~/.config/crev` or `CARGO_CREV_ROOT_DIR_OVERRIDE`
+    pub crev_root_dir: PathBuf,
+    /// Whose trust decides which reviews are exported
+    pub id: Id,
+    pub trust_params: TrustDistanceParams,
+    pub min_trust_level: TrustLevel,
+}
+
+/// How reviewers are described in the `who` field of audits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WhoFormat {
+    /// `"username" (url)` when the reviewer has a verified URL, otherwise a web.crev.dev link
+    #[default]
+    NameUrl,
+    /// Just the crev Id, which is stable regardless of the reviewer's URL
+    CrevIdOnly,
+    /// Always the reviewer's page on web.crev.dev
+    WebLink,
 }
 
 impl Crevette {
@@ -28,13 +298,16 @@ impl Crevette {
     /// Requires a crev Id already set up, and reviews fetched.
     ///
     /// See `cargo crev id new` and `cargo crev repo fetch all`
+    ///
+    /// Uses `trust-params` from crev's user config, if they're set there.
     pub fn new() -> Result<Self, Error> {
         let local = Local::auto_open()?;
         let db = local.load_db()?;
+        let config = local.load_user_config()?;
         Self::new_with_options(
             db,
-            &local.get_current_userid()?,
-            &TrustDistanceParams::default(),
+            config.get_current_userid()?,
+            &config.trust_params(),
             TrustLevel::Low,
         )
     }
@@ -48,378 +321,2285 @@ impl Crevette {
         min_trust_level: TrustLevel,
     ) -> Result<Self, Error> {
         let trusts = db.calculate_trust_set(id, trust_params);
-
-        Ok(Self {
+        Ok(Self::with_trust_set(
             db,
             trusts,
             min_trust_level,
-            include_git_revs: false,
+            Some(id.clone()),
+        ))
+    }
+
+    /// Export reviews trusted from the perspective of any of the given Ids, e.g. of a team that has several crev Ids.
+    ///
+    /// When the Ids trust a reviewer at different levels, the highest level is used.
+    /// There's no single exporting Id, so `set_exclude_self_reviews` has no effect.
+    pub fn new_multi(
+        db: ProofDB,
+        ids: &[Id],
+        trust_params: &TrustDistanceParams,
+        min_trust_level: TrustLevel,
+    ) -> Result<Self, Error> {
+        let mut trust_sets = ids
+            .iter()
+            .map(|id| db.calculate_trust_set(id, trust_params));
+        let mut trusts = trust_sets.next().ok_or(crev_lib::Error::NoIdsGiven)?;
+        for other in trust_sets {
+            merge_trust_set(&mut trusts, other);
+        }
+        Ok(Self::with_trust_set(db, trusts, min_trust_level, None))
+    }
+
+    fn with_trust_set(
+        db: ProofDB,
+        trusts: TrustSet,
+        min_trust_level: TrustLevel,
+        exporting_id: Option<Id>,
+    ) -> Self {
+        let shared_urls = shared_verified_urls(&db, &trusts);
+
+        Self {
+            db: Arc::new(db),
+            trusts: Arc::new(trusts),
+            shared_urls: Arc::new(shared_urls),
+            opts: ConvertOptions {
+                min_trust_level,
+                exporting_id,
+                ..ConvertOptions::default()
+            },
+            crev_root_dir: None,
+            include_header: true,
+            header_override: None,
+            allow_empty: false,
+            emit_checksum: false,
+            progress: None,
+        }
+    }
+
+    /// Like `new()`, but with crev's config dir and Id given explicitly, instead of the current user's.
+    ///
+    /// `convert_into_repo` will write to the proofs repo of that crev config.
+    pub fn new_from_config(config: CrevetteConfig) -> Result<Self, Error> {
+        let local = Local::open_at(&config.crev_root_dir)?;
+        let db = local.load_db()?;
+        let mut crevette =
+            Self::new_with_options(db, &config.id, &config.trust_params, config.min_trust_level)?;
+        crevette.crev_root_dir = Some(config.crev_root_dir);
+        Ok(crevette)
+    }
+
+    /// Export reviews from `*.crev` proof files in the `proofs_dir` directory, which doesn't need to be managed by crev.
+    ///
+    /// URLs that reviewers declare in these proofs are trusted, as if they were your own proofs.
+    pub fn from_proof_db_path(
+        proofs_dir: &std::path::Path,
+        id: &Id,
+        trust_params: &TrustDistanceParams,
+        min_trust_level: TrustLevel,
+    ) -> Result<Self, Error> {
+        Self::new_with_options(
+            Local::load_db_from_dir(proofs_dir)?,
+            id,
+            trust_params,
+            min_trust_level,
+        )
+    }
+
+    /// Copy of this `Crevette` with a different minimum trust level, which shares the loaded db and trust set
+    #[must_use]
+    pub fn with_min_trust_level(&self, min_trust_level: TrustLevel) -> Self {
+        let mut crevette = self.clone();
+        crevette.opts.min_trust_level = min_trust_level;
+        crevette
+    }
+
+    /// Settings of the conversion, for reusing them with `build_audits`
+    pub fn options(&self) -> &ConvertOptions {
+        &self.opts
+    }
+
+    fn converter(&self) -> Converter<'_> {
+        Converter {
+            db: &self.db,
+            trusts: &self.trusts,
+            shared_urls: &self.shared_urls,
+            opts: &self.opts,
+            progress: self.progress.as_deref().map(|p| p as _),
+            dropped: None,
+            without_details: false,
+        }
+    }
+
+    /// Verified URLs used by more than one trusted Id, e.g. when a reviewer rotated keys but kept the repo.
+    /// Audits of these reviewers have their short Id appended to `who`.
+    pub fn shared_reviewer_urls(&self) -> &BTreeMap<String, Vec<Id>> {
+        &self.shared_urls
+    }
+
+    fn open_local(&self) -> Result<Local, Error> {
+        Ok(match &self.crev_root_dir {
+            Some(dir) => Local::open_at(dir)?,
+            None => Local::auto_open()?,
         })
     }
 
+    /// Whether audits link to reviewers' proof repos and digests of their reviews in `aggregated-from`.
+    ///
+    /// Disable to avoid publishing which reviewers you trust. Enabled by default.
+    pub fn set_include_provenance(&mut self, include_provenance: bool) {
+        self.opts.include_provenance = include_provenance;
+    }
+
+    /// Whether `convert_to_toml` starts with a comment. Enabled by default.
+    pub fn set_include_header(&mut self, include_header: bool) {
+        self.include_header = include_header;
+    }
+
+    /// Text of the header comment, instead of the default one that mentions crevette's version.
+    ///
+    /// Lines are turned into comments automatically.
+    pub fn set_header_override(&mut self, header_override: Option<String>) {
+        self.header_override = header_override;
+    }
+
+    /// Export versions reviewed from git as `version@git:rev`.
+    ///
+    /// cargo-vet ignores such audits unless the dependency is from git at the same revision.
+    /// For violations, the revision is added to the notes.
+    pub fn set_include_git_revs(&mut self, include_git_revs: bool) {
+        self.opts.include_git_revs = include_git_revs;
+    }
+
+    /// Certify `safe-to-run` for positive reviews with at least medium understanding, regardless of thoroughness.
+    ///
+    /// `safe-to-deploy` still requires the usual thoroughness, so such reviews certify `safe-to-run` alone.
+    pub fn set_safe_to_run_by_understanding(&mut self, safe_to_run_by_understanding: bool) {
+        self.opts.scoring_policy.safe_to_run_by_understanding = safe_to_run_by_understanding;
+    }
+
+    /// Number of reverse dependencies of crates, e.g. from the crates.io index.
+    ///
+    /// Audits of crates in the map get a "widely used (N reverse deps)" note.
+    pub fn set_reverse_dependencies(&mut self, reverse_dependencies: Option<HashMap<String, u64>>) {
+        self.opts.reverse_dependencies = reverse_dependencies;
+    }
+
+    /// Export reviews of crates from a crates.io mirror registered under a different source URL.
+    ///
+    /// `None` (the default) is `https://crates.io`.
+    pub fn set_source_override(&mut self, source_override: Option<String>) {
+        self.opts.source_override = source_override;
+    }
+
+    /// Shorten notes longer than this many characters, with a reference to the full crev review.
+    ///
+    /// `None` (the default) keeps full notes.
+    pub fn set_max_note_len(&mut self, max_note_len: Option<usize>) {
+        self.opts.max_note_len = max_note_len;
+    }
+
+    /// Download `audits.toml` files from the given URLs, and include their audits in `convert_to_document`.
+    ///
+    /// Audits get the URL in `aggregated-from`. URLs that couldn't be downloaded or parsed are returned with their errors,
+    /// and the rest are still imported.
+    #[cfg(feature = "aggregate")]
+    pub fn with_aggregated_imports(&mut self, urls: Vec<String>) -> Vec<(String, Error)> {
+        let client = match HttpConfig::default().client() {
+            Ok(client) => client,
+            Err(e) => return vec![(urls.join(", "), e)],
+        };
+        let known: Vec<_> = vet::BUILT_IN_CRITERIA
+            .into_iter()
+            .chain(self.opts.criteria().into_keys())
+            .collect();
+        let mut failed = Vec::new();
+        for url in urls {
+            let toml = client
+                .get(&url)
+                .send()
+                .and_then(|res| res.error_for_status())
+                .and_then(|res| res.text())
+                .map_err(|e| {
+                    Error::IO(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("Can't download {url}: {e}"),
+                    ))
+                });
+            let audits = toml.and_then(|toml| {
+                aggregate::parse_audits(&url, &toml, &known)
+                    .map_err(|e| Error::Parse(Box::new((url.clone(), e.to_string()))))
+            });
+            match audits {
+                Ok(audits) => self.opts.aggregated.extend(audits),
+                Err(e) => failed.push((url, e)),
+            }
+        }
+        failed
+    }
+
+    /// Certify `safe-to-run` for neutral reviews by highly trusted reviewers with the same thoroughness and understanding as positive reviews.
+    ///
+    /// Otherwise neutral reviews need more thoroughness and understanding than positive ones.
+    pub fn set_relax_neutral_for_high_trust(&mut self, relax_neutral_for_high_trust: bool) {
+        self.opts.scoring_policy.relax_neutral_for_high_trust = relax_neutral_for_high_trust;
+    }
+
+    /// Certify `safe-to-deploy` only if the reviewer's understanding of the code is at least this level, regardless of the rating.
+    ///
+    /// Defaults to `Level::Medium`. Reviews that fall short may still be `safe-to-run`.
+    pub fn set_min_understanding_for_deploy(&mut self, min_understanding_for_deploy: Level) {
+        self.opts.scoring_policy.min_understanding_for_deploy = min_understanding_for_deploy;
+    }
+
+    /// Export at most this many audits of every crate, preferring newest versions and best reviews.
+    ///
+    /// Violations are always exported, and don't count towards the limit.
+    pub fn set_max_entries_per_crate(&mut self, max_entries_per_crate: Option<usize>) {
+        self.opts.max_entries_per_crate = max_entries_per_crate;
+    }
+
+    /// Let `convert_into_repo` commit an `audits.toml` without any audits. Disabled by default, since it's usually a misconfiguration.
+    pub fn set_allow_empty(&mut self, allow_empty: bool) {
+        self.allow_empty = allow_empty;
+    }
+
+    /// Names of criteria for low, medium, and high trust in reviewers, instead of `DEFAULT_TRUST_CRITERIA_NAMES`.
+    ///
+    /// They're used both in audits and in definitions of the criteria.
+    pub fn set_trust_criteria_names(&mut self, trust_criteria_names: [&'static str; 3]) {
+        self.opts.trust_criteria_names = trust_criteria_names;
+    }
+
+    /// Criteria violated by negative reviews, by the highest severity of issues they report (`Medium` if none).
+    ///
+    /// Severities missing from the map use the default criteria.
+    /// Criteria that crevette doesn't define need definitions in `set_custom_criteria`.
+    pub fn set_violation_criteria(
+        &mut self,
+        violation_criteria: Option<BTreeMap<Level, Vec<&'static str>>>,
+    ) {
+        self.opts.violation_criteria = violation_criteria;
+    }
+
+    /// Definitions of extra criteria to include in the generated files, e.g. ones used in `set_violation_criteria`
+    pub fn set_custom_criteria(
+        &mut self,
+        custom_criteria: BTreeMap<&'static str, vet::CriteriaEntry>,
+    ) {
+        self.opts.custom_criteria = custom_criteria;
+    }
+
+    /// Export only these criteria, e.g. just `safe-to-run` and `safe-to-deploy` for cargo-vet configs that don't define crevette's criteria.
+    ///
+    /// Other criteria are removed from audits and from definitions, and audits left without any criteria are skipped.
+    /// `None` (the default) exports all criteria.
+    pub fn set_allowed_criteria(&mut self, allowed_criteria: Option<HashSet<String>>) {
+        self.opts.allowed_criteria = allowed_criteria;
+    }
+
+    /// Add trust, thoroughness, and understanding of the review, and the score computed from them, to notes of audits
+    pub fn set_annotate_scores(&mut self, annotate_scores: bool) {
+        self.opts.annotate_scores = annotate_scores;
+    }
+
+    /// Export every review that qualifies for an audit, even if a newer version has a better review by a more trusted reviewer.
+    pub fn set_emit_all_reviews(&mut self, emit_all_reviews: bool) {
+        self.opts.emit_all_reviews = emit_all_reviews;
+    }
+
+    /// Add a link to browse the reviewed source code to notes of audits.
+    ///
+    /// Crates from crates.io link to docs.rs. Other registries' sources are included as-is, if they're URLs.
+    pub fn set_include_source_url(&mut self, include_source_url: bool) {
+        self.opts.include_source_url = include_source_url;
+    }
+
+    /// Change how the review's comment and advisories are laid out in notes
+    pub fn set_note_format(&mut self, note_format: NoteFormat) {
+        self.opts.note_format = note_format;
+    }
+
+    /// Versions of crates that have been yanked, e.g. from the crates.io index.
+    ///
+    /// Audits of these versions get a note about it, or are skipped with `set_skip_yanked`.
+    pub fn set_yanked_versions(&mut self, yanked: HashSet<(String, semver::Version)>) {
+        self.opts.yanked = yanked;
+    }
+
+    /// Don't export audits of versions in `set_yanked_versions`. Violations are still exported.
+    pub fn set_skip_yanked(&mut self, skip_yanked: bool) {
+        self.opts.skip_yanked = skip_yanked;
+    }
+
+    /// Export reviews rated neutral. Enabled by default. Without them the file has only positive audits and violations.
+    pub fn set_include_neutral_reviews(&mut self, include_neutral_reviews: bool) {
+        self.opts.include_neutral_reviews = include_neutral_reviews;
+    }
+
+    /// Don't export reviews written by the Id the audits are exported for, so that they're only other people's reviews
+    pub fn set_exclude_self_reviews(&mut self, exclude_self_reviews: bool) {
+        self.opts.exclude_self_reviews = exclude_self_reviews;
+    }
+
+    /// Called with the number of crates converted so far, e.g. to drive a progress bar
+    pub fn set_progress(&mut self, progress: impl Fn(Progress) + Send + Sync + 'static) {
+        self.progress = Some(Arc::new(progress));
+    }
+
+    /// Append to descriptions of crevette's criteria how they're derived from crev reviews, e.g. the score thresholds of `level-*`
+    pub fn set_verbose_criteria_descriptions(&mut self, verbose_criteria_descriptions: bool) {
+        self.opts.verbose_criteria_descriptions = verbose_criteria_descriptions;
+    }
+
+    /// Shape of the audits file. `VetSchema::V1` works with cargo-vet releases that predate `cargo vet aggregate`,
+    /// but it leaves out provenance (`set_include_provenance`) and `aggregated-from` of criteria.
+    pub fn set_vet_schema(&mut self, vet_schema: vet::VetSchema) {
+        self.opts.vet_schema = vet_schema;
+    }
+
+    /// Make `convert_to_document` fail if it drops reviews that met the trust and score thresholds,
+    /// because of a better review of a newer version, a missing proof digest, or ignored violations
+    pub fn set_strict(&mut self, strict: bool) {
+        self.opts.strict = strict;
+    }
+
+    /// Make `convert_to_config_document` suggest requiring `safe-to-deploy` in cargo-vet's policy for crates
+    /// that have a strong `safe-to-deploy` audit by a highly trusted reviewer
+    pub fn set_suggest_policy(&mut self, suggest_policy: bool) {
+        self.opts.suggest_policy = suggest_policy;
+    }
+
+    /// Write `who` as an array even for a single reviewer, for consumers expecting one shape
+    pub fn set_who_always_vec(&mut self, who_always_vec: bool) {
+        self.opts.who_always_vec = who_always_vec;
+    }
+
+    /// Export only crates matching any of the patterns, like `tokio-*` or `serde*`. An empty list exports all crates.
+    pub fn set_crate_glob(&mut self, patterns: Vec<String>) {
+        self.opts.crate_globs = patterns;
+    }
+
+    /// Add a line like "3 advisories: 2 high, 1 medium" to notes of violations with advisories or issues
+    pub fn set_severity_summary(&mut self, severity_summary: bool) {
+        self.opts.severity_summary = severity_summary;
+    }
+
+    /// In notes, call severities "informational", "low", "moderate" and "critical" like advisory databases do,
+    /// instead of crev's "none", "low", "medium" and "high"
+    pub fn set_advisory_severity_names(&mut self, advisory_severity_names: bool) {
+        self.opts.advisory_severity_names = advisory_severity_names;
+    }
+
+    /// Whether documents define the criteria used by audits. Enabled by default.
+    ///
+    /// Disable when the criteria are defined elsewhere, e.g. in a file made with `criteria_toml`.
+    pub fn set_include_criteria_block(&mut self, include_criteria_block: bool) {
+        self.opts.include_criteria_block = include_criteria_block;
+    }
+
+    /// Link used as notes of violations that have no other notes. `{name}` is replaced with the crate name.
+    ///
+    /// Defaults to `DEFAULT_AUDIT_PORTAL_URL_TEMPLATE`.
+    pub fn set_audit_portal_url_template(&mut self, audit_portal_url_template: String) {
+        self.opts.audit_portal_url_template = audit_portal_url_template;
+    }
+
+    /// Also export reviews of crates from these sources (like `https://github.com/org/repo`), for crates used only from git.
+    ///
+    /// Their versions are always exported as `version@git:rev`, regardless of `set_include_git_revs`.
+    /// Reviews from these sources that don't have a git revision are skipped.
+    pub fn set_git_sources(&mut self, git_sources: Vec<String>) {
+        self.opts.git_sources = git_sources;
+    }
+
+    /// Languages (like `de`) that reviewers write their comments in, since crev doesn't record it.
+    ///
+    /// Notes with a comment by one of these reviewers start with a `[lang: de]` marker.
+    pub fn set_reviewer_languages(&mut self, reviewer_languages: HashMap<Id, String>) {
+        self.opts.reviewer_languages = reviewer_languages;
+    }
+
+    /// Let `convert_into_repo` also commit a SHA-256 checksum of the audits file, as `audits.toml.sha256` in `sha256sum` format
+    pub fn set_emit_checksum(&mut self, emit_checksum: bool) {
+        self.emit_checksum = emit_checksum;
+    }
+
+    /// Minimum trust level for reviews of specific crates, e.g. to be stricter about security-critical crates.
+    ///
+    /// Crates that aren't in the map use the minimum trust level given to the constructor.
+    pub fn set_crate_trust_overrides(
+        &mut self,
+        crate_trust_overrides: HashMap<String, TrustLevel>,
+    ) {
+        self.opts.crate_trust_overrides = crate_trust_overrides;
+    }
+
+    /// Change how reviewers are described in the `who` field
+    pub fn set_who_format(&mut self, who_format: WhoFormat) {
+        self.opts.who_format = who_format;
+    }
+
+    /// Also export reviews flagging crates as unmaintained as violations of the `maintained` criterion.
+    ///
+    /// Such a review becomes a pair of entries: its own audit or violation, and the `maintained` violation.
+    /// Audits never certify `maintained`, so these violations don't conflict with positive reviews
+    /// of the same version, which are still exported with the `unmaintained` criterion.
+    /// Like other violations, the `maintained` one is kept even when the review's own audit is dropped,
+    /// e.g. by `set_max_entries_per_crate`. Reviewers whose violations are ignored don't get it.
+    /// Require `maintained` in your cargo-vet policy to block unmaintained dependencies.
+    pub fn set_unmaintained_as_violation(&mut self, unmaintained_as_violation: bool) {
+        self.opts.unmaintained_as_violation = unmaintained_as_violation;
+    }
+
     /// Write `audits.toml` to your current crev repository.
     ///
     /// After `cargo crev publish` the audit will be available in your crev-proofs repo.
     pub fn convert_into_repo(&self) -> Result<RepoInfo, Error> {
-        let toml = self.convert_to_toml()?;
-        let local = Local::auto_open()?;
-        let path = local.get_proofs_dir_path()?;
-        let audit_path = path.join("audits.toml");
-        if let Err(e) = std::fs::write(&audit_path, toml) {
+        let local = self.open_local()?;
+        self.convert_into_repo_at(&local.get_proofs_dir_path()?)
+    }
+
+    /// Same as `convert_into_repo`, but writes the audits to `file_name` (like `deploy-audits.toml`) instead of `audits.toml`
+    pub fn convert_into_repo_named(&self, file_name: &str) -> Result<RepoInfo, Error> {
+        let local = self.open_local()?;
+        self.convert_into_repo_at_named(&local.get_proofs_dir_path()?, file_name)
+    }
+
+    /// Write and commit `audits.toml` in a git checkout of a crev-proofs repo at the given path
+    ///
+    /// Fails with `Error::NoReviewsExported` instead of committing a file without any audits, unless `set_allow_empty` is enabled.
+    pub fn convert_into_repo_at(&self, proofs_dir: &std::path::Path) -> Result<RepoInfo, Error> {
+        self.convert_into_repo_at_named(proofs_dir, "audits.toml")
+    }
+
+    /// Same as `convert_into_repo_at`, but the file is called `file_name`, which is relative to `proofs_dir`
+    pub fn convert_into_repo_at_named(
+        &self,
+        proofs_dir: &std::path::Path,
+        file_name: &str,
+    ) -> Result<RepoInfo, Error> {
+        let doc = self.convert_to_document()?;
+        if doc.audits.is_empty() && !self.allow_empty {
+            return Err(Error::NoReviewsExported);
+        }
+        let toml = self.document_to_toml(&doc)?;
+        let audit_path = proofs_dir.join(file_name);
+        let checksum = self
+            .emit_checksum
+            .then(|| sha256sum_line(toml.as_bytes(), file_name));
+        if let Err(e) = write_atomically(&audit_path, toml.as_bytes()) {
             return Err(Error::FileWrite(e, audit_path));
         }
-        local.proof_dir_git_add_path("audits.toml".as_ref())?;
-        local.proof_dir_commit("Updated audits.toml")?;
-
-        let mut repo_git_url = Local::url_for_repo_at_path(&path).ok();
-        if let Some(u) = &repo_git_url {
-            if let Some((host, rest)) = u.strip_prefix("git@").and_then(|u| u.split_once(':')) {
-                repo_git_url = Some(format!("https://{host}/{rest}"));
+        Local::git_add_path_in_repo_at_path(proofs_dir, file_name.as_ref())?;
+        if let Some(checksum) = checksum {
+            let checksum_file_name = format!("{file_name}.sha256");
+            let checksum_path = proofs_dir.join(&checksum_file_name);
+            if let Err(e) = write_atomically(&checksum_path, checksum.as_bytes()) {
+                return Err(Error::FileWrite(e, checksum_path));
             }
+            Local::git_add_path_in_repo_at_path(proofs_dir, checksum_file_name.as_ref())?;
         }
+        Local::git_commit_in_repo_at_path(proofs_dir, &format!("Updated {file_name}"))?;
+
+        let repo_git_url = match Local::url_for_repo_at_path(proofs_dir) {
+            Ok(u) => Some(https_repo_url(&u)),
+            // Only a missing `origin` is expected, other failures mean the repo is broken
+            Err(crev_lib::Error::Git(e)) if e.code() == git2::ErrorCode::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
 
         let (repo_https_url, repo_name) = repo_git_url
             .as_deref()
-            .and_then(|u| {
-                let u = u.trim_end_matches('/').trim_end_matches(".git");
-                if let Some(rest) = u.strip_prefix("https://github.com/") {
-                    Some((
-                        format!("https://raw.githubusercontent.com/{rest}/HEAD/audits.toml"),
-                        rest.split('/').next().unwrap_or_default().into(),
-                    ))
-                } else {
-                    u.strip_prefix("https://gitlab.com/").map(|rest| (
-                        format!("https://gitlab.com/{rest}/-/raw/HEAD/audits.toml"),
-                        rest.split('/').next().unwrap_or_default().into(),
-                    ))
-                }
-            })
+            .and_then(|u| raw_audits_url(u, file_name))
             .unzip();
+        let url_status = match (&repo_git_url, &repo_https_url) {
+            (None, _) => UrlStatus::NoRemote,
+            (Some(_), None) => UrlStatus::Unsupported,
+            (Some(_), Some(_)) => UrlStatus::Supported,
+        };
 
         Ok(RepoInfo {
             local_path: audit_path,
             repo_git_url,
             repo_https_url,
+            url_status,
             repo_name,
         })
     }
 
     /// Here's your cargo-vet-compatible `audits.toml` file
     pub fn convert_to_toml(&self) -> Result<String, Error> {
-        let mut toml = toml_edit::ser::to_string_pretty(&self.convert_to_document()?)
-            .map_err(|toml| Error::IO(io::Error::new(io::ErrorKind::Other, toml)))?;
+        self.document_to_toml(&self.convert_to_document()?)
+    }
 
-        toml.insert_str(0, &format!("# Automatically generated by https://lib.rs/crevette {} from cargo-crev reviews\n\n", env!("CARGO_PKG_VERSION")));
+    /// The same TOML as `convert_to_toml`, with a report of what's in it
+    pub fn convert_with_report(&self) -> Result<(String, ConversionReport), Error> {
+        let (doc, dropped_reviews) = self.converter().convert_to_document_with_dropped()?;
+        let toml = self.document_to_toml(&doc)?;
 
-        Ok(toml)
+        let mut report = ConversionReport {
+            dropped_reviews,
+            sha256: sha256_hex(toml.as_bytes()),
+            ..Default::default()
+        };
+        for (name, entries) in &doc.audits {
+            report.entry_count += entries.len();
+            report.entries_per_crate.insert(name.clone(), entries.len());
+            report.reviewers.extend(
+                entries
+                    .iter()
+                    .flat_map(|e| e.who.as_vec())
+                    .map(String::from),
+            );
+        }
+        Ok((toml, report))
     }
 
-    #[cfg(feature = "debcargo")]
-    pub fn from_debcargo_repo(temp_dir_path: &std::path::Path) -> Result<String, Error> {
-        let _ = std::fs::create_dir_all(&temp_dir_path);
-
-        let deb_err = |e: index_debcargo::Error| Error::ErrorIteratingLocalProofStore(Box::new((temp_dir_path.into(), e.to_string())));
-        let mut d = index_debcargo::Index::new(temp_dir_path).map_err(deb_err)?;
+    fn document_to_toml(&self, doc: &vet::AuditsFile) -> Result<String, Error> {
+        let mut toml = to_toml(doc)?;
 
-        let sources_file = temp_dir_path.join("Sources.gz");
-        if !sources_file.exists() {
-            let sources_file_tmp = temp_dir_path.join("Sources.gz.tmp");
-            let sources_url = "https://deb.debian.org/debian/dists/stable/main/source/Sources.gz";
-            let mut out = std::fs::File::create(&sources_file_tmp)?;
-            let dl_err = |e| Error::IO(io::Error::new(io::ErrorKind::Other, format!("Can't download {sources_url}: {e}")));
-            let mut response = match reqwest::blocking::get(sources_url) {
-                Ok(r) => r,
-                Err(e) => return Err(dl_err(e)),
+        if self.include_header {
+            let header = match &self.header_override {
+                Some(header) => header.lines().map(|line| format!("# {line}\n")).collect(),
+                None => format!("# Automatically generated by https://lib.rs/crevette {} from cargo-crev reviews\n", env!("CARGO_PKG_VERSION")),
             };
-            response.copy_to(&mut out).map_err(dl_err)?;
-            std::fs::rename(&sources_file_tmp, &sources_file)?;
+            toml.insert_str(0, &format!("{header}\n"));
         }
-        let sources_gzipped = std::fs::File::open(&sources_file)?;
-        let sources = flate2::read::GzDecoder::new(sources_gzipped);
 
-        d.add_distro_source("stable", io::BufReader::new(sources)).map_err(deb_err)?;
+        Ok(toml)
+    }
 
-        let debs = d.list_all().map_err(deb_err)?;
+    /// Definitions of the criteria used by the audits, for sharing one `[criteria]` table between audits files.
+    ///
+    /// Keys are criteria names, without the `criteria.` prefix.
+    pub fn criteria_toml(&self) -> Result<String, Error> {
+        to_toml(&self.opts.criteria())
+    }
 
-        let mut audits = BTreeMap::new();
-        let mut seen = std::collections::HashSet::new();
-        for d in debs {
-            let mut who = vec![];
-            seen.clear();
-            if let Some(email) = d.maintainer_email {
-                who.push(format!("\"{}\" <{email}>", d.maintainer_name.as_deref().unwrap_or_default()));
-                seen.insert(email);
-                if let Some(name) = d.maintainer_name {
-                    seen.insert(name);
-                }
-            }
-            for a in &d.uploaders {
-                let a = cargo_author::Author::new(a);
-                if let Some(email) = a.email {
-                    let uploader = format!("\"{}\" <{email}>", a.name.as_deref().unwrap_or_default());
-                    if let Some(name) = a.name {
-                        if !seen.insert(name) { continue; }
-                    }
-                    if !seen.insert(email) { continue; }
-                    who.push(uploader);
-                }
-            }
+    /// Advisory scaffolding for cargo-vet's `config.toml`, to be edited before use.
+    ///
+    /// It's empty unless `set_suggest_policy` is enabled.
+    pub fn convert_to_config_document(&self) -> vet::ConfigFile {
+        self.converter().convert_to_config_document()
+    }
 
-            let distros = d.distros.join(", ");
-            let distros = if distros.is_empty() { "unreleased" } else { &distros };
+    /// `convert_to_config_document` as TOML
+    pub fn convert_to_config_toml(&self) -> Result<String, Error> {
+        let mut toml = to_toml(&self.convert_to_config_document())?;
+        toml.insert_str(0, &format!("# Suggested by https://lib.rs/crevette {}. Review before adding to cargo-vet's config.toml\n\n", env!("CARGO_PKG_VERSION")));
+        Ok(toml)
+    }
 
-            audits.entry(d.name).or_insert_with(Vec::new).push(vet::AuditEntry {
-                criteria: vec!["safe-to-run", "safe-to-deploy"],
-                aggregated_from: vec![index_debcargo::DEBCARGO_CONF_REPO_URL.to_string()],
-                notes: Some(format!("Packaged for Debian ({distros}). Changelog:\n{}", d.changelog)),
-                delta: None,
-                version: Some(d.version),
-                violation: None,
-                who: vet::StringOrVec::Vec(who),
-            });
-        }
+    /// Same audits as `convert_to_toml`, but as JSON
+    pub fn convert_to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(&self.convert_to_document()?)
+            .map_err(|e| Error::JsonSerialization(e.to_string()))
+    }
+
+    /// Crates packaged in Debian, in the suite and from the mirror set in `opts`.
+    ///
+    /// The package list is cached in `temp_dir_path` as `Sources.gz` for `stable`, and `Sources-{suite}.gz` for other suites.
+    /// In offline mode it must already be there, and nothing will be downloaded.
+    #[cfg(feature = "debcargo")]
+    pub fn from_debcargo_repo(
+        temp_dir_path: &std::path::Path,
+        opts: &DebianOptions,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<String, Error> {
+        let DebianOptions {
+            suite,
+            mirror,
+            offline,
+            max_cache_age,
+            http,
+        } = opts;
+        let sources_url = debian_sources_url(suite, mirror.as_deref())?;
 
+        let _ = std::fs::create_dir_all(temp_dir_path);
 
-        let audits = vet::AuditsFile {
-            criteria: Default::default(),
-            audits,
+        // Only stable was supported before, so its cache keeps the old name
+        let sources_file = if suite == "stable" {
+            temp_dir_path.join("Sources.gz")
+        } else {
+            temp_dir_path.join(format!("Sources-{suite}.gz"))
         };
+        if *offline {
+            if !sources_file.exists() {
+                return Err(missing_in_offline_mode(&sources_file));
+            }
+        } else {
+            remove_stale_cache(&sources_file, *max_cache_age, std::time::SystemTime::now())?;
+            download_if_modified(&http.client()?, &sources_url, &sources_file)?;
+        }
+        let audits = Self::debcargo_audits_from_sources(
+            temp_dir_path,
+            suite,
+            std::fs::File::open(&sources_file)?,
+            progress,
+        )?;
 
-        let mut toml = toml_edit::ser::to_string_pretty(&audits)
-            .map_err(|toml| Error::IO(io::Error::new(io::ErrorKind::Other, toml)))?;
+        importer_toml(audits.audits, "debcargo-conf repo")
+    }
 
-        toml.insert_str(0, &format!("# Automatically generated by https://lib.rs/crevette {} from debcargo-conf repo\n\n", env!("CARGO_PKG_VERSION")));
+    /// Audits of crates in a gzipped Debian `Sources` file of the given `suite`, without downloading it.
+    ///
+    /// `temp_dir_path` is used by `index_debcargo` for its data about debcargo packages. This doesn't touch the network.
+    ///
+    /// `progress` may be called from multiple threads, so its counts can arrive out of order.
+    #[cfg(feature = "debcargo")]
+    pub fn debcargo_audits_from_sources<R: io::Read>(
+        temp_dir_path: &std::path::Path,
+        suite: &str,
+        sources_gzipped: R,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<vet::AuditsFile, Error> {
+        use rayon::prelude::*;
 
-        Ok(toml)
-    }
+        let deb_err = |e: index_debcargo::Error| {
+            Error::PackageIndex(Box::new((temp_dir_path.into(), e.to_string())))
+        };
+        let mut d = index_debcargo::Index::new(temp_dir_path).map_err(deb_err)?;
 
-    #[cfg(feature = "guix")]
-    pub fn from_guix_repo(temp_dir_path: &std::path::Path) -> Result<String, Error> {
-        let _ = std::fs::create_dir_all(&temp_dir_path);
+        let sources = flate2::read::GzDecoder::new(sources_gzipped);
+        d.add_distro_source(suite, io::BufReader::new(sources))
+            .map_err(deb_err)?;
 
-        let g_err = |e: index_guix::Error| Error::ErrorIteratingLocalProofStore(Box::new((temp_dir_path.into(), e.to_string())));
-        let g = index_guix::Index::new(temp_dir_path).map_err(g_err)?;
+        let debs: Vec<_> = d.list_all().map_err(deb_err)?.into_iter().collect();
+
+        let total = debs.len();
+        let done = std::sync::atomic::AtomicUsize::new(0);
+        // Order of the collected entries doesn't depend on the threads
+        let entries: Vec<_> = debs
+            .into_par_iter()
+            .map(|d| {
+                report_progress(
+                    progress,
+                    done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1,
+                    total,
+                );
+                let mut who = debian_who(d.maintainer_name, d.maintainer_email, &d.uploaders);
+                if who.is_empty() {
+                    who.push("Debian".into());
+                }
+
+                let distros = d.distros.join(", ");
+                let distros = if distros.is_empty() {
+                    "unreleased"
+                } else {
+                    &distros
+                };
+
+                (
+                    d.name,
+                    vet::AuditEntry {
+                        criteria: vec!["safe-to-deploy", "safe-to-run"],
+                        aggregated_from: vec![index_debcargo::DEBCARGO_CONF_REPO_URL.to_string()],
+                        notes: Some(format!(
+                            "Packaged for Debian ({distros}). Changelog:\n{}",
+                            d.changelog
+                        )),
+                        delta: None,
+                        version: Some(d.version),
+                        violation: None,
+                        who: vet::StringOrVec::Vec(who),
+                    },
+                )
+            })
+            .collect();
+
+        let mut audits = BTreeMap::new();
+        for (name, entry) in entries {
+            audits.entry(name).or_insert_with(Vec::new).push(entry);
+        }
+
+        Ok(vet::AuditsFile {
+            criteria: Default::default(),
+            audits,
+        })
+    }
+
+    /// Only packages in the given Guix `categories` are exported, if they're given, e.g. `&["rust"]`
+    ///
+    /// In `offline` mode the index-guix checkout in `temp_dir_path` must already exist, because `index_guix` would fetch it.
+    #[cfg(feature = "guix")]
+    pub fn from_guix_repo(
+        temp_dir_path: &std::path::Path,
+        categories: Option<&[&str]>,
+        offline: bool,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<String, Error> {
+        let _ = std::fs::create_dir_all(temp_dir_path);
+
+        if offline {
+            let checkout = guix_checkout_dir(temp_dir_path);
+            if !checkout.exists() {
+                return Err(missing_in_offline_mode(&checkout));
+            }
+        }
+
+        let g_err = |e: index_guix::Error| {
+            Error::PackageIndex(Box::new((temp_dir_path.into(), e.to_string())))
+        };
+        let g = index_guix::Index::new(temp_dir_path).map_err(g_err)?;
+
+        let all: Vec<_> = g
+            .list_all()
+            .map_err(g_err)?
+            .into_iter()
+            .filter(|(category, _)| is_selected_guix_category(category, categories))
+            .collect();
+
+        let total = all.iter().map(|(_, packages)| packages.len()).sum();
+        let mut done = 0;
+        let mut audits = BTreeMap::new();
+        for (category, packages) in all {
+            for p in packages {
+                done += 1;
+                report_progress(progress, done, total);
+                audits
+                    .entry(p.name)
+                    .or_insert_with(Vec::new)
+                    .push(vet::AuditEntry {
+                        criteria: vec!["safe-to-run"],
+                        aggregated_from: vec![index_guix::GUIX_REPO_URL.to_string()],
+                        notes: Some(format!("Packaged for Guix ({category})")),
+                        delta: None,
+                        version: Some(p.version),
+                        violation: None,
+                        who: vet::StringOrVec::String("Guix".into()),
+                    });
+            }
+        }
+
+        importer_toml(audits, "guix repo")
+    }
+
+    /// Crates packaged as `rust-*` source packages in openSUSE Tumbleweed
+    ///
+    /// The package list is cached in `temp_dir_path` as `opensuse-primary.xml.gz`.
+    /// In offline mode it must already be there, and nothing will be downloaded.
+    #[cfg(feature = "opensuse")]
+    pub fn from_opensuse_repo(
+        temp_dir_path: &std::path::Path,
+        opts: &OpensuseOptions,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<String, Error> {
+        use std::io::Read;
+
+        let _ = std::fs::create_dir_all(temp_dir_path);
+
+        let o_err =
+            |e: io::Error| Error::PackageIndex(Box::new((temp_dir_path.into(), e.to_string())));
+        let primary_file = temp_dir_path.join("opensuse-primary.xml.gz");
+        if opts.offline {
+            if !primary_file.exists() {
+                return Err(missing_in_offline_mode(&primary_file));
+            }
+        } else {
+            let client = opts.http.client()?;
+            let primary_url = opensuse::primary_url(&client).map_err(o_err)?;
+            remove_stale_cache(
+                &primary_file,
+                opts.max_cache_age,
+                std::time::SystemTime::now(),
+            )?;
+            download_if_modified(&client, &primary_url, &primary_file)?;
+        }
+        let mut primary = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(&primary_file)?)
+            .read_to_string(&mut primary)
+            .map_err(o_err)?;
+
+        let all = opensuse::list_all(&primary);
+        let total = all.len();
+        let mut audits = BTreeMap::new();
+        for (i, c) in all.into_iter().enumerate() {
+            report_progress(progress, i + 1, total);
+            audits
+                .entry(c.name)
+                .or_insert_with(Vec::new)
+                .push(vet::AuditEntry {
+                    criteria: vec!["safe-to-run"],
+                    aggregated_from: vec![opensuse::obs_package_url(&c.package)],
+                    notes: Some(format!(
+                        "Packaged for openSUSE ({} {})",
+                        opensuse::OBS_PROJECT,
+                        c.package
+                    )),
+                    delta: None,
+                    version: Some(c.version),
+                    violation: None,
+                    who: vet::StringOrVec::String("openSUSE".into()),
+                });
+        }
+
+        importer_toml(audits, "openSUSE repo")
+    }
+
+    /// In `offline` mode the aports checkout in `temp_dir_path` is used as-is, and must already exist.
+    #[cfg(feature = "alpine")]
+    pub fn from_alpine_repo(
+        temp_dir_path: &std::path::Path,
+        offline: bool,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<String, Error> {
+        let _ = std::fs::create_dir_all(temp_dir_path);
+
+        let a_err =
+            |e: io::Error| Error::PackageIndex(Box::new((temp_dir_path.into(), e.to_string())));
+        let aports_dir = if offline {
+            let aports_dir = alpine::aports_dir(temp_dir_path);
+            if !aports_dir.exists() {
+                return Err(missing_in_offline_mode(&aports_dir));
+            }
+            aports_dir
+        } else {
+            alpine::update_aports(temp_dir_path)?
+        };
+
+        let all = alpine::list_all(&aports_dir).map_err(a_err)?;
+
+        // Many packages vendor the same crate version
+        let mut origins = BTreeMap::new();
+        for c in all {
+            origins
+                .entry((c.name, c.version))
+                .or_insert_with(Vec::new)
+                .push(c.origin);
+        }
+
+        let total = origins.len();
+        let mut audits = BTreeMap::new();
+        for (i, ((name, version), mut origins)) in origins.into_iter().enumerate() {
+            report_progress(progress, i + 1, total);
+            origins.sort();
+            origins.dedup();
+            audits
+                .entry(name)
+                .or_insert_with(Vec::new)
+                .push(vet::AuditEntry {
+                    criteria: vec!["safe-to-run"],
+                    aggregated_from: vec![alpine::APORTS_REPO_URL.to_string()],
+                    notes: Some(format!("Packaged for Alpine edge ({})", origins.join(", "))),
+                    delta: None,
+                    version: Some(version),
+                    violation: None,
+                    who: vet::StringOrVec::String("Alpine Linux".into()),
+                });
+        }
+
+        importer_toml(audits, "Alpine aports repo")
+    }
+
+    /// In `offline` mode the homebrew-core checkout in `temp_dir_path` is used as-is, and must already exist.
+    #[cfg(feature = "homebrew")]
+    pub fn from_homebrew_repo(
+        temp_dir_path: &std::path::Path,
+        offline: bool,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<String, Error> {
+        let _ = std::fs::create_dir_all(temp_dir_path);
+
+        let h_err =
+            |e: io::Error| Error::PackageIndex(Box::new((temp_dir_path.into(), e.to_string())));
+        let homebrew_core_dir = if offline {
+            let homebrew_core_dir = homebrew::homebrew_core_dir(temp_dir_path);
+            if !homebrew_core_dir.exists() {
+                return Err(missing_in_offline_mode(&homebrew_core_dir));
+            }
+            homebrew_core_dir
+        } else {
+            homebrew::update_homebrew_core(temp_dir_path)?
+        };
+
+        let all = homebrew::list_all(&homebrew_core_dir).map_err(h_err)?;
+
+        // Formulae may share crate versions
+        let mut formulae = BTreeMap::new();
+        for c in all {
+            formulae
+                .entry((c.name, c.version))
+                .or_insert_with(Vec::new)
+                .push(c.formula);
+        }
+
+        let total = formulae.len();
+        let mut audits = BTreeMap::new();
+        for (i, ((name, version), mut formulae)) in formulae.into_iter().enumerate() {
+            report_progress(progress, i + 1, total);
+            formulae.sort();
+            formulae.dedup();
+            audits
+                .entry(name)
+                .or_insert_with(Vec::new)
+                .push(vet::AuditEntry {
+                    criteria: vec!["safe-to-run"],
+                    aggregated_from: vec![homebrew::HOMEBREW_CORE_REPO_URL.to_string()],
+                    notes: Some(format!(
+                        "Packaged in homebrew (formula {})",
+                        formulae.join(", ")
+                    )),
+                    delta: None,
+                    version: Some(version),
+                    violation: None,
+                    who: vet::StringOrVec::String("Homebrew".into()),
+                });
+        }
+
+        importer_toml(audits, "homebrew-core repo")
+    }
+
+    /// Crates that recipes of the given conda-forge `feedstocks` download from crates.io, e.g. `&["ripgrep"]` for `ripgrep-feedstock`.
+    ///
+    /// In `offline` mode the feedstock checkouts in `temp_dir_path` are used as-is, and must already exist.
+    #[cfg(feature = "conda")]
+    pub fn from_condaforge_repo(
+        temp_dir_path: &std::path::Path,
+        feedstocks: &[&str],
+        offline: bool,
+        progress: Option<&(dyn Fn(Progress) + Sync)>,
+    ) -> Result<String, Error> {
+        let _ = std::fs::create_dir_all(temp_dir_path);
+
+        let c_err =
+            |e: io::Error| Error::PackageIndex(Box::new((temp_dir_path.into(), e.to_string())));
+        for &feedstock in feedstocks {
+            if offline {
+                let feedstock_dir = conda::feedstock_dir(temp_dir_path, feedstock);
+                if !feedstock_dir.exists() {
+                    return Err(missing_in_offline_mode(&feedstock_dir));
+                }
+            } else {
+                conda::update_feedstock(temp_dir_path, feedstock)?;
+            }
+        }
+
+        let all = conda::list_all(temp_dir_path, feedstocks).map_err(c_err)?;
+
+        // Feedstocks may share crate versions
+        let mut by_version = BTreeMap::new();
+        for c in all {
+            by_version
+                .entry((c.name, c.version))
+                .or_insert_with(Vec::new)
+                .push(c.feedstock);
+        }
+
+        let total = by_version.len();
+        let mut audits = BTreeMap::new();
+        for (i, ((name, version), mut feedstocks)) in by_version.into_iter().enumerate() {
+            report_progress(progress, i + 1, total);
+            feedstocks.sort();
+            feedstocks.dedup();
+            audits
+                .entry(name)
+                .or_insert_with(Vec::new)
+                .push(vet::AuditEntry {
+                    criteria: vec!["safe-to-run"],
+                    aggregated_from: feedstocks.iter().map(|f| conda::feedstock_url(f)).collect(),
+                    notes: Some(format!(
+                        "Packaged for conda-forge ({})",
+                        feedstocks
+                            .iter()
+                            .map(|f| format!("{f}-feedstock"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                    delta: None,
+                    version: Some(version),
+                    violation: None,
+                    who: vet::StringOrVec::String("conda-forge".into()),
+                });
+        }
+
+        importer_toml(audits, "conda-forge feedstocks")
+    }
+
+    /// Baseline `safe-to-run` audits of crates.io crates listed as `pkg:cargo/name@version` components of a JSON SBOM.
+    ///
+    /// Suppliers of the components are the `who` of the audits, or the SBOM itself if a component has no supplier.
+    #[cfg(feature = "sbom")]
+    pub fn from_sbom(path: &std::path::Path, format: SbomFormat) -> Result<String, Error> {
+        let json = std::fs::read_to_string(path)?;
+        let all = sbom::list_all(&json, format)
+            .map_err(|e| Error::Parse(Box::new((path.display().to_string(), e.to_string()))))?;
+
+        // The same crate may be a component of several parts of the product
+        let mut suppliers = BTreeMap::new();
+        for c in all {
+            suppliers
+                .entry((c.name, c.version))
+                .or_insert_with(Vec::new)
+                .extend(c.supplier);
+        }
+
+        let sbom_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut audits = BTreeMap::new();
+        for ((name, version), mut suppliers) in suppliers {
+            suppliers.sort();
+            suppliers.dedup();
+            // cargo-vet needs someone to vouch for it, so it's whoever made the SBOM
+            if suppliers.is_empty() {
+                suppliers.push(format!("SBOM {sbom_name}"));
+            }
+            audits
+                .entry(name)
+                .or_insert_with(Vec::new)
+                .push(vet::AuditEntry {
+                    criteria: vec!["safe-to-run"],
+                    aggregated_from: vec![],
+                    notes: Some(format!("Listed in SBOM {sbom_name}")),
+                    delta: None,
+                    version: Some(version),
+                    violation: None,
+                    who: vet::StringOrVec::Vec(suppliers),
+                });
+        }
+
+        importer_toml(audits, "SBOM")
+    }
+
+    /// Baseline audits by `who` for every crates.io dependency locked in the given `Cargo.lock`.
+    ///
+    /// Path and git dependencies, and packages without a checksum, are skipped.
+    #[cfg(feature = "cargo-lock")]
+    pub fn from_cargo_lock(
+        path: &std::path::Path,
+        who: &str,
+        criteria: Vec<&'static str>,
+    ) -> Result<String, Error> {
+        let lock = std::fs::read_to_string(path)?;
+        let audits = audits_from_cargo_lock(&lock, who, &criteria)
+            .map_err(|e| Error::Parse(Box::new((path.display().to_string(), e.to_string()))))?;
+
+        importer_toml(audits, "Cargo.lock")
+    }
+
+    /// All exported audits and violations.
+    ///
+    /// crev has no separate proof for distrusting a package: a negative review is that signal,
+    /// and it becomes a violation with the review's comment (or a link to the crate's reviews) in notes.
+    /// Issue reports (neutral or unrated reviews with issues) are violations too, with the issues' severity.
+    /// Distrust of reviewers only removes their reviews, including negative ones. See `distrusted_reviews`.
+    pub fn convert_to_document(&self) -> Result<vet::AuditsFile, Error> {
+        self.converter().convert_to_document()
+    }
+
+    /// Audits split into files by their strongest criterion: `safe-to-deploy`, `safe-to-run`, `violations`,
+    /// and `other` for audits that certify neither built-in criterion.
+    ///
+    /// Files without any audits are left out.
+    pub fn convert_to_documents_by_criteria(
+        &self,
+    ) -> Result<BTreeMap<String, vet::AuditsFile>, Error> {
+        self.converter().convert_to_documents_by_criteria()
+    }
+
+    /// Audits only from reviews written after the given date, for incremental regeneration.
+    ///
+    /// This isn't a filter for a compliance window: the result is meant to be merged into a document
+    /// exported previously at `after`, so reviews are only compared against other new reviews.
+    pub fn convert_to_document_since(&self, after: &Date) -> Result<vet::AuditsFile, Error> {
+        self.converter().convert_to_document_since(after)
+    }
+
+    /// Trusted publishers, instead of audits of individual versions, for highly trusted reviewers who publish crates.
+    ///
+    /// `publishers` maps crev Ids to their crates.io accounts. Entries are valid from `start` to `end` (`YYYY-MM-DD`).
+    /// The result is for the `[[trusted]]` section of cargo-vet's `audits.toml`.
+    pub fn convert_to_trusted_document(
+        &self,
+        publishers: &HashMap<Id, CratesIoPublisher>,
+        start: &str,
+        end: &str,
+    ) -> vet::TrustedFile {
+        self.converter()
+            .convert_to_trusted_document(publishers, start, end)
+    }
+
+    /// Reviews that would be considered for export, but are skipped because the db has no digest of their proof.
+    ///
+    /// This shouldn't happen, so any reviews here point to a stale or corrupted index of proofs.
+    pub fn reviews_without_digest(&self) -> Vec<PkgVersionReviewId> {
+        self.converter().reviews_without_digest()
+    }
+
+    /// Non-negative reviews written by reviewers that are distrusted in the web of trust, sorted by crate.
+    ///
+    /// These are never exported, but they may be worth a warning, since someone vouched for these crates.
+    pub fn distrusted_reviews(&self) -> Vec<&Package> {
+        self.converter().distrusted_reviews()
+    }
+
+    /// Only violations from negative reviews, without any positive audits. Works as a deny list.
+    pub fn convert_violations_to_document(&self) -> Result<vet::AuditsFile, Error> {
+        self.converter().convert_violations_to_document()
+    }
+
+    /// Number of audit entries `convert_to_document` would emit, without building their notes and provenance.
+    ///
+    /// Progress isn't reported.
+    pub fn estimate_entry_count(&self) -> usize {
+        self.converter().estimate_entry_count()
+    }
+
+    /// Audits as `(crate name, entry)`, generated lazily crate by crate, without building a whole document.
+    ///
+    /// Entries for the same crate are adjacent, ordered from the newest version.
+    pub fn audit_entries(&self) -> impl Iterator<Item = (String, vet::AuditEntry)> + '_ {
+        self.converter().audit_entries()
+    }
+
+    /// Reviews that `convert_to_document` turns into audits, per crate, starting from the preferred one.
+    ///
+    /// For building other output formats from the same selection of reviews. Aggregated audits aren't included.
+    pub fn selected_reviews(&self) -> BTreeMap<String, Vec<&Package>> {
+        self.converter().selected_reviews()
+    }
+
+    /// Names of all crates that have at least one audit in `convert_to_document`.
+    ///
+    /// Cheaper than generating the audits, for comparing against a dependency tree.
+    pub fn covered_crates(&self) -> BTreeSet<String> {
+        self.converter().covered_crates()
+    }
+
+    /// The review that gives the first audit of every crate in `convert_to_document`
+    pub fn best_review_per_crate(&self) -> BTreeMap<String, &Package> {
+        self.converter().best_review_per_crate()
+    }
+
+    /// Reviews that meet the minimum trust level, with the scores used to select them, per crate.
+    ///
+    /// The first review of every crate is the preferred one: of the newest version, most trusted, and most thorough.
+    /// Low-scoring reviews may still be left out of the audits.
+    pub fn scored_reviews(&self) -> BTreeMap<String, Vec<ScoredReview<'_>>> {
+        self.converter().scored_reviews()
+    }
+
+    /// Effective trust in the author of the review, if the review is known
+    pub fn trust_for_review(&self, review_id: &PkgVersionReviewId) -> Option<TrustLevel> {
+        self.converter().trust_for_review(review_id)
+    }
+}
+
+impl ConvertOptions {
+    fn source(&self) -> &str {
+        self.source_override.as_deref().unwrap_or(SOURCE_CRATES_IO)
+    }
+
+    fn is_allowed_criterion(&self, criterion: &str) -> bool {
+        self.allowed_criteria
+            .as_ref()
+            .map_or(true, |allowed| allowed.contains(criterion))
+    }
+
+    /// Removes criteria that aren't allowed, and returns whether the audit should still be exported
+    fn retain_allowed_criteria(&self, entry: &mut vet::AuditEntry) -> bool {
+        if self.allowed_criteria.is_none() {
+            return true;
+        }
+        entry.criteria.retain(|c| self.is_allowed_criterion(c));
+        !entry.criteria.is_empty()
+    }
+
+    fn criteria(&self) -> BTreeMap<&'static str, vet::CriteriaEntry> {
+        let mut criteria = standard_criteria(self.trust_criteria_names);
+        if self.verbose_criteria_descriptions {
+            for (name, c) in &mut criteria {
+                if let (Some(description), Some(derivation)) = (
+                    &mut c.description,
+                    criterion_derivation(name, self.trust_criteria_names),
+                ) {
+                    *description = format!("{description}. {derivation}").into();
+                }
+            }
+        }
+        criteria.extend(
+            self.custom_criteria
+                .iter()
+                .map(|(&name, c)| (name, c.clone())),
+        );
+        criteria.retain(|name, c| {
+            c.implies
+                .retain(|implied| self.is_allowed_criterion(implied));
+            self.is_allowed_criterion(name)
+        });
+        criteria
+    }
+
+    fn audit_portal_url(&self, crate_name: &str) -> String {
+        self.audit_portal_url_template.replace("{name}", crate_name)
+    }
+
+    fn is_selected_crate(&self, name: &str) -> bool {
+        self.crate_globs.is_empty()
+            || self
+                .crate_globs
+                .iter()
+                .any(|pattern| glob_matches(pattern, name))
+    }
+
+    fn is_yanked(&self, package: &PackageInfo) -> bool {
+        !self.yanked.is_empty()
+            && self
+                .yanked
+                .contains(&(package.id.id.name.clone(), package.id.version.clone()))
+    }
+
+    fn is_git_source(&self, pkg: &PackageInfo) -> bool {
+        self.git_sources.contains(&pkg.id.id.source)
+    }
+
+    fn vet_version(&self, pkg: &PackageInfo) -> String {
+        if let Some(rev) = self.git_revision(pkg) {
+            format!("{}@git:{rev}", pkg.id.version)
+        } else {
+            pkg.id.version.to_string()
+        }
+    }
+
+    fn git_revision<'a>(&self, pkg: &'a PackageInfo) -> Option<&'a str> {
+        ((self.include_git_revs || self.is_git_source(pkg))
+            && pkg.revision_type == "git"
+            && !pkg.revision.is_empty())
+        .then_some(pkg.revision.as_str())
+    }
+}
+
+impl<'a> Converter<'a> {
+    /// Reviews from the registry source and the git sources
+    fn pkg_reviews(self) -> impl Iterator<Item = &'a Package> + 'a {
+        std::iter::once(self.opts.source())
+            .chain(self.opts.git_sources.iter().map(String::as_str))
+            .flat_map(move |source| self.db.get_pkg_reviews_for_source(source))
+    }
+
+    fn convert_to_document(&self) -> Result<vet::AuditsFile, Error> {
+        if !self.opts.strict {
+            return self.document_from_entries(
+                self.audit_entries()
+                    .chain(self.opts.aggregated.iter().cloned()),
+            );
+        }
+        self.convert_to_document_with_dropped().map(|(doc, _)| doc)
+    }
+
+    /// Also returns reviews that qualified, but were dropped. They're an error in strict mode.
+    fn convert_to_document_with_dropped(&self) -> Result<(vet::AuditsFile, Vec<String>), Error> {
+        let dropped = std::cell::RefCell::new(Vec::new());
+        let converter = Converter {
+            dropped: Some(&dropped),
+            ..*self
+        };
+        let doc = converter.document_from_entries(
+            converter
+                .audit_entries()
+                .chain(self.opts.aggregated.iter().cloned()),
+        )?;
+        let dropped = dropped.into_inner();
+        if self.opts.strict && !dropped.is_empty() {
+            return Err(Error::QualifiedReviewsDropped(dropped));
+        }
+        Ok((doc, dropped))
+    }
+
+    /// Same selection as `convert_to_document`, including filtering of allowed criteria in `document`
+    fn estimate_entry_count(&self) -> usize {
+        let converter = Converter {
+            progress: None,
+            without_details: true,
+            ..*self
+        };
+        converter
+            .audit_entries()
+            .chain(self.opts.aggregated.iter().cloned())
+            .filter(|(_, entry)| {
+                self.opts.allowed_criteria.is_none()
+                    || entry
+                        .criteria
+                        .iter()
+                        .any(|c| self.opts.is_allowed_criterion(c))
+            })
+            .count()
+    }
+
+    fn convert_to_documents_by_criteria(&self) -> Result<BTreeMap<String, vet::AuditsFile>, Error> {
+        let mut entries = BTreeMap::<&str, Vec<_>>::new();
+        for (name, entry) in self
+            .audit_entries()
+            .chain(self.opts.aggregated.iter().cloned())
+        {
+            let file = if entry.violation.is_some() {
+                "violations"
+            } else if entry.criteria.contains(&"safe-to-deploy") {
+                "safe-to-deploy"
+            } else if entry.criteria.contains(&"safe-to-run") {
+                "safe-to-run"
+            } else {
+                "other"
+            };
+            entries.entry(file).or_default().push((name, entry));
+        }
+        entries
+            .into_iter()
+            .map(|(file, entries)| {
+                Ok((
+                    file.to_string(),
+                    self.document_from_entries(entries.into_iter())?,
+                ))
+            })
+            .collect()
+    }
+
+    fn convert_to_document_since(&self, after: &Date) -> Result<vet::AuditsFile, Error> {
+        self.document_from_entries(self.audit_entries_for(self.reviews_by_crate(Some(after))))
+    }
+
+    /// Validated against crevette's criteria, even if they're left out of the document
+    fn document(
+        &self,
+        mut audits: BTreeMap<String, Vec<vet::AuditEntry>>,
+    ) -> Result<vet::AuditsFile, Error> {
+        if self.opts.allowed_criteria.is_some() {
+            audits.retain(|_, entries| {
+                entries.retain_mut(|entry| self.opts.retain_allowed_criteria(entry));
+                !entries.is_empty()
+            });
+        }
+        let mut doc = vet::AuditsFile {
+            criteria: self.opts.criteria(),
+            audits,
+        };
+        doc.validate()?;
+        if !self.opts.include_criteria_block {
+            doc.criteria.clear();
+        }
+        doc.downgrade_to(self.opts.vet_schema);
+        Ok(doc)
+    }
+
+    fn document_from_entries(
+        &self,
+        entries: impl Iterator<Item = (String, vet::AuditEntry)>,
+    ) -> Result<vet::AuditsFile, Error> {
+        // audits BTreeMap will sort reviews by crate
+        let mut audits = BTreeMap::default();
+        for (name, entry) in entries {
+            audits.entry(name).or_insert_with(Vec::new).push(entry);
+        }
+
+        self.document(audits)
+    }
+
+    fn convert_to_trusted_document(
+        &self,
+        publishers: &HashMap<Id, CratesIoPublisher>,
+        start: &str,
+        end: &str,
+    ) -> vet::TrustedFile {
+        let mut trusted = BTreeMap::<String, Vec<vet::TrustEntry>>::new();
+        for id in self.trusts.iter_trusted_ids() {
+            if self.trusts.get_effective_trust_level(id) < TrustLevel::High {
+                continue;
+            }
+            let Some(publisher) = publishers.get(id) else {
+                continue;
+            };
+            for crate_name in &publisher.crates {
+                trusted
+                    .entry(crate_name.clone())
+                    .or_default()
+                    .push(vet::TrustEntry {
+                        criteria: vec!["safe-to-deploy"],
+                        user_id: publisher.user_id,
+                        start: start.into(),
+                        end: end.into(),
+                        notes: Some(format!(
+                            "Publisher is highly trusted in the crev web of trust as {id}"
+                        )),
+                    });
+            }
+        }
+        for entries in trusted.values_mut() {
+            entries.sort_by_key(|e| e.user_id);
+        }
+        vet::TrustedFile { trusted }
+    }
+
+    fn reviews_without_digest(&self) -> Vec<PkgVersionReviewId> {
+        self.reviews_by_crate(None)
+            .into_values()
+            .flatten()
+            .map(|s| PkgVersionReviewId::from(s.review))
+            .filter(|id| self.db.get_proof_digest_by_pkg_review_id(id).is_none())
+            .collect()
+    }
+
+    fn distrusted_reviews(&self) -> Vec<&'a Package> {
+        let mut reviews: Vec<_> = self
+            .pkg_reviews()
+            .filter(|r| exported_review(r).is_some_and(|review| !is_violation(r, review)))
+            .filter(|r| self.trusts.is_distrusted(&r.common.from.id))
+            .collect();
+        reviews.sort_by(|a, b| {
+            a.package
+                .id
+                .id
+                .name
+                .cmp(&b.package.id.id.name)
+                .then(b.package.id.version.cmp(&a.package.id.version))
+        });
+        reviews
+    }
+
+    fn convert_violations_to_document(&self) -> Result<vet::AuditsFile, Error> {
+        let mut audits = BTreeMap::default();
+        for mut reviews_for_crate in self.reviews_by_crate(None).into_values() {
+            reviews_for_crate.retain(|s| {
+                exported_review(s.review).is_some_and(|review| is_violation(s.review, review))
+            });
+            sort_by_preference(&mut reviews_for_crate);
+            for ScoredReview {
+                review: r,
+                trust,
+                review_quality_score,
+            } in reviews_for_crate
+            {
+                if let Some(entry) = self.audit_entry(trust, review_quality_score, r, &mut None) {
+                    audits
+                        .entry(r.package.id.id.name.clone())
+                        .or_insert_with(Vec::new)
+                        .push(entry);
+                }
+            }
+        }
+
+        self.document(audits)
+    }
+
+    fn audit_entries(self) -> impl Iterator<Item = (String, vet::AuditEntry)> + 'a {
+        self.audit_entries_for(self.reviews_by_crate(None))
+    }
+
+    fn selected_reviews(&self) -> BTreeMap<String, Vec<&'a Package>> {
+        let mut selected = BTreeMap::<String, Vec<&'a Package>>::new();
+        for (r, mut entry) in self.reviewed_entries_for(self.reviews_by_crate(None)) {
+            if !self.opts.retain_allowed_criteria(&mut entry) {
+                continue;
+            }
+            let reviews = selected.entry(r.package.id.id.name.clone()).or_default();
+            // A review can be exported both as an audit and an unmaintained violation
+            if !reviews.last().is_some_and(|last| std::ptr::eq(*last, r)) {
+                reviews.push(r);
+            }
+        }
+        selected
+    }
+
+    fn audit_entries_for(
+        self,
+        reviews_by_crate: HashMap<&'a PackageId, Vec<ScoredReview<'a>>>,
+    ) -> impl Iterator<Item = (String, vet::AuditEntry)> + 'a {
+        self.reviewed_entries_for(reviews_by_crate)
+            .map(|(r, entry)| (r.package.id.id.name.clone(), entry))
+    }
+
+    fn reviewed_entries_for(
+        self,
+        reviews_by_crate: HashMap<&'a PackageId, Vec<ScoredReview<'a>>>,
+    ) -> impl Iterator<Item = (&'a Package, vet::AuditEntry)> + 'a {
+        let total = reviews_by_crate.len();
+        reviews_by_crate
+            .into_values()
+            .enumerate()
+            .flat_map(move |(i, mut reviews_for_crate)| {
+                report_progress(self.progress, i + 1, total);
+                sort_by_preference(&mut reviews_for_crate);
+
+                let mut last_review = None;
+                let mut audits_left = self.opts.max_entries_per_crate.unwrap_or(usize::MAX);
+                reviews_for_crate.into_iter().flat_map(
+                    move |ScoredReview {
+                              review: r,
+                              trust,
+                              review_quality_score,
+                          }| {
+                        // Violations are never dropped
+                        let entry = self
+                            .audit_entry(trust, review_quality_score, r, &mut last_review)
+                            .filter(|entry| {
+                                if entry.violation.is_some() {
+                                    return true;
+                                }
+                                if audits_left == 0 {
+                                    return false;
+                                }
+                                audits_left -= 1;
+                                true
+                            });
+                        let unmaintained = if self.opts.unmaintained_as_violation {
+                            self.unmaintained_violation(r)
+                        } else {
+                            None
+                        };
+                        entry
+                            .into_iter()
+                            .chain(unmaintained)
+                            .map(move |entry| (r, entry))
+                    },
+                )
+            })
+    }
+
+    fn convert_to_config_document(&self) -> vet::ConfigFile {
+        let mut config = vet::ConfigFile::default();
+        if !self.opts.suggest_policy {
+            return config;
+        }
+        let trust_high = self.opts.trust_criteria_names[2];
+        for (r, entry) in self.reviewed_entries_for(self.reviews_by_crate(None)) {
+            let is_strong_deploy = entry.violation.is_none()
+                && [trust_high, "strong", "safe-to-deploy"]
+                    .iter()
+                    .all(|c| entry.criteria.contains(c));
+            if is_strong_deploy {
+                config.policy.entry(r.package.id.id.name.clone()).or_insert_with(|| vet::PolicyEntry {
+                    criteria: vec!["safe-to-deploy"],
+                    notes: Some(format!("Suggested by crevette: strong review of {} by a highly trusted reviewer", r.package.id.version)),
+                });
+            }
+        }
+        config
+    }
+
+    fn covered_crates(&self) -> BTreeSet<String> {
+        self.reviews_by_crate(None)
+            .into_iter()
+            .filter(|(_, reviews_for_crate)| reviews_for_crate.iter().any(|s| self.is_exported(s)))
+            .map(|(pkg_id, _)| pkg_id.name.clone())
+            .collect()
+    }
+
+    fn best_review_per_crate(&self) -> BTreeMap<String, &'a Package> {
+        self.scored_reviews()
+            .into_iter()
+            .filter_map(|(name, reviews_for_crate)| {
+                let best = reviews_for_crate
+                    .into_iter()
+                    .find(|s| self.is_exported(s))?;
+                Some((name, best.review))
+            })
+            .collect()
+    }
+
+    /// Whether the review is exported as an audit or a violation. Reviews preferred over it don't matter.
+    fn is_exported(
+        &self,
+        &ScoredReview {
+            review: r,
+            trust,
+            review_quality_score,
+        }: &ScoredReview<'_>,
+    ) -> bool {
+        let entry = self.audit_entry(trust, review_quality_score, r, &mut None);
+        let unmaintained = if self.opts.unmaintained_as_violation {
+            self.unmaintained_violation(r)
+        } else {
+            None
+        };
+        entry
+            .into_iter()
+            .chain(unmaintained)
+            .any(|mut entry| self.opts.retain_allowed_criteria(&mut entry))
+    }
+
+    fn scored_reviews(&self) -> BTreeMap<String, Vec<ScoredReview<'a>>> {
+        self.reviews_by_crate(None)
+            .into_iter()
+            .map(|(pkg_id, mut reviews_for_crate)| {
+                sort_by_preference(&mut reviews_for_crate);
+                (pkg_id.name.clone(), reviews_for_crate)
+            })
+            .collect()
+    }
+
+    fn trust_for_review(&self, review_id: &PkgVersionReviewId) -> Option<TrustLevel> {
+        let r = self.db.get_pkg_review_by_pkg_review_id(review_id)?;
+        Some(self.trusts.get_effective_trust_level(&r.common.from.id))
+    }
+
+    /// `after` skips reviews written at or before that date
+    fn reviews_by_crate(
+        &self,
+        after: Option<&Date>,
+    ) -> HashMap<&'a PackageId, Vec<ScoredReview<'a>>> {
+        let mut all = HashMap::new();
+
+        for r in self.pkg_reviews() {
+            let Some(review) = exported_review(r) else {
+                log_skipped(r, "no review");
+                continue;
+            };
+            if !self.opts.is_selected_crate(&r.package.id.id.name) {
+                log_skipped(r, "the crate doesn't match the crate patterns");
+                continue;
+            }
+            if self.opts.exclude_self_reviews
+                && self.opts.exporting_id.as_ref() == Some(&r.common.from.id)
+            {
+                log_skipped(r, "it's a self-review");
+                continue;
+            }
+            if review.rating == Rating::Neutral
+                && !self.opts.include_neutral_reviews
+                && !is_violation(r, review)
+            {
+                log_skipped(r, "neutral reviews aren't exported");
+                continue;
+            }
+            if self.opts.is_git_source(&r.package) && self.opts.git_revision(&r.package).is_none() {
+                log_skipped(
+                    r,
+                    "the crate is from git, but the review has no git revision",
+                );
+                continue;
+            }
+            if after.is_some_and(|after| r.common.date <= *after) {
+                log::trace!(
+                    "Skipping {:?}: written before the last export",
+                    PkgVersionReviewId::from(r)
+                );
+                continue;
+            }
+
+            let trust = self.trusts.get_effective_trust_level(&r.common.from.id);
+            let min_trust_level = self
+                .opts
+                .crate_trust_overrides
+                .get(&r.package.id.id.name)
+                .copied()
+                .unwrap_or(self.opts.min_trust_level);
+            if trust < min_trust_level {
+                log_skipped(
+                    r,
+                    format_args!("trust {trust:?} is below {min_trust_level:?}"),
+                );
+                continue;
+            }
+
+            let review_quality_score =
+                level_as_score(review.thoroughness) + level_as_score(review.understanding);
+            all.entry(&r.package.id.id)
+                .or_insert_with(Vec::new)
+                .push(ScoredReview {
+                    review: r,
+                    trust,
+                    review_quality_score,
+                });
+        }
+        all
+    }
+
+    /// `last_review` is the previously exported review of the same crate, if it's a candidate for being better than this one
+    fn audit_entry(
+        &self,
+        trust: TrustLevel,
+        review_quality_score: u32,
+        r: &Package,
+        last_review: &mut Option<(u32, TrustLevel, semver::Version)>,
+    ) -> Option<vet::AuditEntry> {
+        let review = exported_review(r)?;
+
+        let pub_id = &r.common.from;
+
+        let violation = is_violation(r, review);
+        if !violation && self.opts.skip_yanked && self.opts.is_yanked(&r.package) {
+            log_skipped(r, "the version has been yanked");
+            return None;
+        }
+        let mut criteria = if violation {
+            let severity = r
+                .issues
+                .iter()
+                .map(|i| i.severity)
+                .chain(r.advisories.iter().map(|a| a.severity))
+                .max()
+                .unwrap_or(Level::Medium);
+            match self
+                .opts
+                .violation_criteria
+                .as_ref()
+                .and_then(|m| m.get(&severity))
+            {
+                Some(criteria) => criteria.clone(),
+                None => default_violation_criteria(severity),
+            }
+        } else {
+            if !meets_min_score(trust, review, review_quality_score) {
+                log_skipped(
+                    r,
+                    format_args!("score {review_quality_score} is too low for trust {trust:?}"),
+                );
+                return None;
+            }
+
+            // Avoid exporting pareto-worse reviews
+            if !self.opts.emit_all_reviews {
+                if let Some((l_review_quality_score, l_trust, ref l_version)) = *last_review {
+                    if l_review_quality_score >= review_quality_score
+                        && ((*l_version > r.package.id.version && l_trust >= trust)
+                            || (*l_version >= r.package.id.version && l_trust > trust))
+                    {
+                        self.drop_qualified(
+                            r,
+                            format_args!("a better review of {l_version} is already exported"),
+                        );
+                        return None;
+                    }
+                }
+            }
+
+            criteria_for_non_negative_review(
+                trust,
+                r,
+                review,
+                review_quality_score,
+                self.opts.scoring_policy,
+                self.opts.trust_criteria_names,
+            )
+        };
+        // Stable order keeps regenerated files diffable
+        criteria.sort_unstable();
+
+        let public_url = self.db.lookup_url(&pub_id.id).verified();
+
+        if violation && is_ignored_violation_author(public_url) {
+            self.drop_qualified(r, "violations of this reviewer are ignored");
+            return None;
+        }
+
+        let (version, delta) = if violation {
+            (None, None)
+        } else if let Some(base) = &r.diff_base {
+            let (base_version, version) = (
+                self.opts.vet_version(base),
+                self.opts.vet_version(&r.package),
+            );
+            if base_version == version {
+                self.drop_qualified(r, "the diff base is the same version");
+                return None;
+            }
+            // A downgrade stays one, because only moving from the diff base to the reviewed version is certified
+            (None, Some(format!("{base_version} -> {version}")))
+        } else {
+            (Some(self.opts.vet_version(&r.package)), None)
+        };
+
+        let Some(digest) = self
+            .db
+            .get_proof_digest_by_pkg_review_id(&PkgVersionReviewId::from(r))
+        else {
+            self.drop_qualified(r, "digest of the proof is missing");
+            return None;
+        };
+
+        let mut notes = if self.without_details {
+            None
+        } else {
+            self.notes(r, review, trust, review_quality_score, violation)
+        };
+
+        if let (Some(notes), Some(max_len)) = (notes.as_mut(), self.opts.max_note_len) {
+            truncate_notes(notes, max_len, digest);
+        }
+
+        let entry = vet::AuditEntry {
+            violation: violation.then(|| format!("={}", r.package.id.version)),
+            who: self.who_field(pub_id, public_url),
+            criteria,
+            notes: notes.or_else(|| {
+                violation
+                    .then(|| format!("<{}>", self.opts.audit_portal_url(&r.package.id.id.name)))
+            }),
+            aggregated_from: self.provenance(pub_id, public_url, digest),
+            version,
+            delta,
+        };
+        // Candidate for being a better review than the next one
+        *last_review = (review.rating > Rating::Neutral
+            && r.diff_base.is_none()
+            && r.package.id.version.pre.is_empty())
+        .then_some((review_quality_score, trust, r.package.id.version.clone()));
+        Some(entry)
+    }
+
+    /// Review's comment, followed by advisories and other details, in the configured `NoteFormat`
+    fn notes(
+        &self,
+        r: &Package,
+        review: &Review,
+        trust: TrustLevel,
+        review_quality_score: u32,
+        violation: bool,
+    ) -> Option<String> {
+        let comment = Some(r.comment.as_str()).filter(|c| !c.trim_start().is_empty());
+
+        let advisories = r
+            .advisories
+            .iter()
+            .map(|adv| (adv.severity, non_empty_ids(&adv.ids), adv.comment.trim()));
+        let issues = r.issues.iter().map(|issue| {
+            (
+                issue.severity,
+                non_empty_ids([&issue.id]),
+                issue.comment.trim(),
+            )
+        });
+        // The same problem is often reported both as an advisory and an issue
+        let mut seen = BTreeSet::new();
+        let problems: Vec<_> = advisories
+            .chain(issues)
+            .filter(|(severity, ids, comment)| seen.insert((*severity, ids.clone(), *comment)))
+            .collect();
+
+        let mut details = Vec::new();
+        if violation && self.opts.severity_summary {
+            details.extend(severity_summary(
+                problems.iter().map(|&(severity, _, _)| severity),
+                self.opts.advisory_severity_names,
+            ));
+        }
+        let mut alternatives: Vec<_> = r
+            .alternatives
+            .iter()
+            .map(|alt| {
+                if alt.source == self.opts.source() {
+                    alt.name.clone()
+                } else {
+                    format!("{} ({})", alt.name, alt.source)
+                }
+            })
+            .collect();
+        alternatives.sort();
+        for alt in alternatives {
+            details.push(format!("Alternative suggested: {alt}"));
+        }
+
+        // cargo-vet's violation is a version requirement, so it can't say which git tree has been flagged
+        if violation {
+            if let Some(rev) = self.opts.git_revision(&r.package) {
+                details.push(format!("Reviewed at git revision {rev}"));
+            }
+        }
+
+        if let Some(count) = self
+            .opts
+            .reverse_dependencies
+            .as_ref()
+            .and_then(|rdeps| rdeps.get(&r.package.id.id.name))
+        {
+            details.push(format!("widely used ({count} reverse deps)"));
+        }
+
+        if self.opts.include_source_url {
+            if let Some(url) = source_url(&r.package) {
+                details.push(format!("source: {url}"));
+            }
+        }
+
+        if self.opts.is_yanked(&r.package) {
+            details.push("This version has been yanked".into());
+        }
+
+        if self.opts.annotate_scores {
+            details.push(format!(
+                "crev: trust={trust:?}, thoroughness={:?}, understanding={:?} (score {review_quality_score})",
+                review.thoroughness, review.understanding
+            ));
+        }
+
+        let notes = match self.opts.note_format {
+            NoteFormat::Plain => {
+                let problems = problems.iter().map(|(severity, ids, comment)| {
+                    let mut block = format!(
+                        "severity: {}\n",
+                        severity_name(*severity, self.opts.advisory_severity_names)
+                    );
+                    if !ids.is_empty() {
+                        block.push_str(&format!(
+                            "id: {}\n",
+                            ids.iter()
+                                .map(|id| advisory_id_with_url(id))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                    if !comment.is_empty() {
+                        block.push('\n');
+                        block.push_str(comment);
+                    }
+                    block
+                });
+                comment
+                    .map(String::from)
+                    .into_iter()
+                    .chain(problems)
+                    .chain(details)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            NoteFormat::Markdown => {
+                let mut sections = Vec::new();
+                if let Some(comment) = comment {
+                    sections.push(format!("## Reviewer comment\n\n{}", comment.trim_end()));
+                }
+                if !problems.is_empty() {
+                    let items = problems
+                        .iter()
+                        .map(|(severity, ids, comment)| {
+                            let mut item = format!(
+                                "- **{}**",
+                                severity_name(*severity, self.opts.advisory_severity_names)
+                            );
+                            if !ids.is_empty() {
+                                let ids = ids
+                                    .iter()
+                                    .map(|&id| match advisory_url(id) {
+                                        Some(url) => format!("[{id}]({url})"),
+                                        None => id.to_string(),
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                item.push(' ');
+                                item.push_str(&ids);
+                            }
+                            if !comment.is_empty() {
+                                item.push_str(": ");
+                                // Continuation lines stay in the list item
+                                item.push_str(&comment.replace('\n', "\n  "));
+                            }
+                            item
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    sections.push(format!("## Advisories\n\n{items}"));
+                }
+                if !details.is_empty() {
+                    let items = details
+                        .iter()
+                        .map(|d| format!("- {d}"))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    sections.push(format!("## Details\n\n{items}"));
+                }
+                sections.join("\n\n")
+            }
+        };
+        // Everything other than the comment is generated in English
+        let notes = match comment.and(self.opts.reviewer_languages.get(&r.common.from.id)) {
+            Some(lang) if self.opts.note_format == NoteFormat::Markdown => {
+                format!("[lang: {lang}]\n\n{notes}")
+            }
+            Some(lang) => format!("[lang: {lang}] {notes}"),
+            None => notes,
+        };
+        Some(notes).filter(|n| !n.is_empty())
+    }
 
-        let all = g.list_all().map_err(g_err)?;
+    /// Like `log_skipped`, but for reviews that qualified for an audit, which fail the conversion in strict mode
+    fn drop_qualified(&self, r: &Package, reason: impl std::fmt::Display) {
+        if let Some(dropped) = self.dropped {
+            dropped.borrow_mut().push(format!(
+                "{} {} by {}: {reason}",
+                r.package.id.id.name, r.package.id.version, r.common.from.id
+            ));
+        }
+        log_skipped(r, reason);
+    }
 
-        let mut audits = BTreeMap::new();
-        for (category, packages) in all {
-            for p in packages {
-                audits.entry(p.name).or_insert_with(Vec::new).push(vet::AuditEntry {
-                    criteria: vec!["safe-to-run"],
-                    aggregated_from: vec![index_guix::GUIX_REPO_URL.to_string()],
-                    notes: Some(format!("Packaged for Guix ({category})")),
-                    delta: None,
-                    version: Some(p.version),
-                    violation: None,
-                    who: vet::StringOrVec::Vec(vec![]),
-                });
+    /// The URL is looked up by the reviewer's Id, so all audits of a reviewer who moved their repo have the most recent URL.
+    ///
+    /// Reviewers sharing a verified URL would be indistinguishable, so their short Id is added
+    fn who(&self, pub_id: &PublicId, public_url: Option<&Url>) -> String {
+        let who = author_from_id(pub_id, public_url, self.opts.who_format);
+        match public_url {
+            Some(url)
+                if self.opts.who_format == WhoFormat::NameUrl
+                    && self.shared_urls.contains_key(&url.url) =>
+            {
+                let id = pub_id.id.to_string();
+                format!("{who} [{}]", id.get(..8).unwrap_or(&id))
             }
+            _ => who,
         }
+    }
 
-        let audits = vet::AuditsFile {
-            criteria: Default::default(),
-            audits,
-        };
+    fn who_field(&self, pub_id: &PublicId, public_url: Option<&Url>) -> vet::StringOrVec {
+        let who = self.who(pub_id, public_url);
+        if self.opts.who_always_vec {
+            vet::StringOrVec::Vec(vec![who])
+        } else {
+            vet::StringOrVec::String(who)
+        }
+    }
 
-        let mut toml = toml_edit::ser::to_string_pretty(&audits)
-            .map_err(|toml| Error::IO(io::Error::new(io::ErrorKind::Other, toml)))?;
+    /// Violation of the `maintained` criterion, if the review flags the crate as unmaintained
+    fn unmaintained_violation(&self, r: &Package) -> Option<vet::AuditEntry> {
+        if !r.flags.unmaintained {
+            return None;
+        }
+        let pub_id = &r.common.from;
+        let public_url = self.db.lookup_url(&pub_id.id).verified();
+        if is_ignored_violation_author(public_url) {
+            self.drop_qualified(r, "violations of this reviewer are ignored");
+            return None;
+        }
+        let digest = self
+            .db
+            .get_proof_digest_by_pkg_review_id(&PkgVersionReviewId::from(r))?;
 
-        toml.insert_str(0, &format!("# Automatically generated by https://lib.rs/crevette {} from guix repo\n\n", env!("CARGO_PKG_VERSION")));
+        Some(vet::AuditEntry {
+            violation: Some(format!("={}", r.package.id.version)),
+            who: self.who_field(pub_id, public_url),
+            criteria: vec!["maintained"],
+            notes: Some(format!(
+                "Flagged as unmaintained <{}>",
+                self.opts.audit_portal_url(&r.package.id.id.name)
+            )),
+            aggregated_from: self.provenance(pub_id, public_url, digest),
+            version: None,
+            delta: None,
+        })
+    }
 
-        Ok(toml)
+    /// Reviewer's proof repo and the review's digest, unless hidden
+    fn provenance(
+        &self,
+        pub_id: &PublicId,
+        public_url: Option<&Url>,
+        digest: &Digest,
+    ) -> Vec<String> {
+        if !self.opts.include_provenance || self.without_details {
+            return vec![];
+        }
+        let base_url = public_url
+            .map(|u| format!("{}#{}", u.url, pub_id.id))
+            .unwrap_or_else(|| web_profile_url(&pub_id.id));
+        vec![base_url, format!("crev:review/{}", digest.to_base64())]
     }
+}
 
-    pub fn convert_to_document(&self) -> Result<vet::AuditsFile, Error> {
-        // audits BTreeMap will sort reviews by crate
-        let mut all = HashMap::new();
+#[cfg(feature = "cargo-lock")]
+#[derive(serde::Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
 
-        for r in self.db.get_pkg_reviews_for_source(SOURCE_CRATES_IO) {
-            let Some(review) = r.review() else { continue };
+#[cfg(feature = "cargo-lock")]
+#[derive(serde::Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+}
 
-            let trust = self.trusts.get_effective_trust_level(&r.common.from.id);
-            if trust < self.min_trust_level {
-                continue;
-            }
+#[cfg(feature = "cargo-lock")]
+fn audits_from_cargo_lock(
+    lock: &str,
+    who: &str,
+    criteria: &[&'static str],
+) -> Result<BTreeMap<String, Vec<vet::AuditEntry>>, toml_edit::de::Error> {
+    let lock: CargoLock = toml_edit::de::from_str(lock)?;
 
-            let review_quality_score = level_as_score(review.thoroughness) + level_as_score(review.understanding);
-            all.entry(&r.package.id.id).or_insert_with(Vec::new).push((trust, review_quality_score, r));
+    let mut audits = BTreeMap::new();
+    for p in lock.package {
+        let from_crates_io = matches!(
+            p.source.as_deref(),
+            Some(
+                "registry+https://github.com/rust-lang/crates.io-index"
+                    | "sparse+https://index.crates.io/"
+            )
+        );
+        if !from_crates_io || p.checksum.is_none() {
+            continue;
         }
-
-        let mut audits = BTreeMap::default();
-        for reviews_for_crate in all.values_mut() {
-            reviews_for_crate.sort_by(|(a_trust, q_a, a), (b_trust, q_b, b)| {
-                b.package.id.version.cmp(&a.package.id.version)
-                    .then(b_trust.cmp(a_trust))
-                    .then(q_b.cmp(q_a))
-                    .then(b.common.date.cmp(&a.common.date))
+        audits
+            .entry(p.name)
+            .or_insert_with(Vec::new)
+            .push(vet::AuditEntry {
+                criteria: criteria.to_vec(),
+                aggregated_from: vec![],
+                notes: Some("Used in a locked project".into()),
+                delta: None,
+                version: Some(p.version),
+                violation: None,
+                who: vet::StringOrVec::String(who.into()),
             });
+    }
+    Ok(audits)
+}
 
-            let mut last_review = None;
-            for &(trust, review_quality_score, r) in &*reviews_for_crate {
-                let Some(review) = r.review() else { continue };
-
-                let pub_id = &r.common.from;
-
-                let violation = review.rating == Rating::Negative;
-                let criteria = if violation {
-                    let severity = r.issues.iter().map(|i| i.severity)
-                        .chain(r.advisories.iter().map(|a| a.severity))
-                        .max().unwrap_or(Level::Medium);
-                    match severity {
-                        Level::None => vec!["level-none"], // not sure if that makes sense
-                        Level::Low => vec!["level-low"],
-                        Level::Medium => vec!["safe-to-deploy"],
-                        Level::High => vec!["safe-to-run", "safe-to-deploy"],
-                    }
-                } else {
-                    let min_score = match trust {
-                        TrustLevel::Distrust | TrustLevel::None => continue,
-                        TrustLevel::Low => level_as_score(Level::High),
-                        TrustLevel::Medium => level_as_score(Level::Medium),
-                        TrustLevel::High => level_as_score(Level::Low),
-                    } + match review.rating {
-                        Rating::Negative => level_as_score(Level::None),
-                        Rating::Neutral => level_as_score(Level::Medium),
-                        Rating::Positive => level_as_score(Level::Low),
-                        Rating::Strong => level_as_score(Level::None),
-                    };
-
-                    if review_quality_score < min_score {
-                        continue;
-                    }
-
-                    // Avoid exporting pareto-worse reviews
-                    if let Some((l_review_quality_score, l_trust, ref l_version)) = last_review {
-                        if l_review_quality_score >= review_quality_score {
-                            if *l_version > r.package.id.version && l_trust >= trust {
-                                continue;
-                            }
-                            if *l_version >= r.package.id.version && l_trust > trust {
-                                continue;
-                            }
-                        }
-                    }
-
-                    criteria_for_non_negative_review(trust, r, review, review_quality_score)
-                };
+/// A crev review considered for export
+#[derive(Debug, Clone, Copy)]
+pub struct ScoredReview<'a> {
+    pub review: &'a Package,
+    /// Effective trust in the author of the review
+    pub trust: TrustLevel,
+    /// Combined thoroughness and understanding
+    pub review_quality_score: u32,
+}
 
-                let public_url = self.db.lookup_url(&pub_id.id).verified();
-                let base_url = public_url
-                    .map(|u| format!("{}#{}", u.url, pub_id.id))
-                    .unwrap_or_else(|| format!("crev:user/{}", pub_id.id));
+fn sort_by_preference(reviews_for_crate: &mut [ScoredReview<'_>]) {
+    reviews_for_crate.sort_by(|a, b| {
+        b.review
+            .package
+            .id
+            .version
+            .cmp(&a.review.package.id.version)
+            .then(b.trust.cmp(&a.trust))
+            .then(b.review_quality_score.cmp(&a.review_quality_score))
+            .then(b.review.common.date.cmp(&a.review.common.date))
+            // Makes the output deterministic when different reviewers tie on everything else
+            .then_with(|| a.review.common.from.id.cmp(&b.review.common.from.id))
+    });
+}
 
-                if violation && public_url.map_or(false, |u| u.url.contains("MaulingM")) {
-                    continue;
-                }
+/// Where the reviewed version of the crate can be browsed
+fn source_url(package: &PackageInfo) -> Option<String> {
+    let id = &package.id;
+    if id.id.source == SOURCE_CRATES_IO {
+        return Some(format!(
+            "https://docs.rs/crate/{}/{}/source/",
+            id.id.name, id.version
+        ));
+    }
+    let source = id.id.source.trim();
+    (source.starts_with("https://") || source.starts_with("http://")).then(|| source.to_string())
+}
 
-                let (version, delta) = if violation {
-                    (None, None)
-                } else if let Some(base) = &r.diff_base {
-                    (
-                        None,
-                        Some(format!(
-                            "{} -> {}",
-                            self.vet_version(base),
-                            self.vet_version(&r.package)
-                        )),
-                    )
-                } else {
-                    (Some(self.vet_version(&r.package)), None)
-                };
+fn default_violation_criteria(severity: Level) -> Vec<&'static str> {
+    match severity {
+        Level::None => vec!["level-none"], // not sure if that makes sense
+        Level::Low => vec!["level-low"],
+        Level::Medium => vec!["safe-to-deploy"],
+        Level::High => vec!["safe-to-run", "safe-to-deploy"],
+    }
+}
 
-                let Some(digest) = self
-                    .db
-                    .get_proof_digest_by_pkg_review_id(&PkgVersionReviewId::from(r))
-                else {
-                    continue;
-                };
+/// The review's rating, including issue reports (`cargo crev crate issue`) that don't rate anything
+fn exported_review(r: &Package) -> Option<&Review> {
+    r.review()
+        .or_else(|| (!r.issues.is_empty()).then(|| r.review_possibly_none()))
+}
 
-                let mut notes = Some(&r.comment)
-                    .filter(|c| !c.trim_start().is_empty())
-                    .cloned();
+/// Negative reviews are violations, and so are neutral reviews that report issues in the reviewed version.
+///
+/// A neutral rating with issues doesn't vouch for the crate, so it's better exported as a warning than as an audit.
+/// Advisories alone don't count, since they're about fixes of older versions.
+fn is_violation(r: &Package, review: &Review) -> bool {
+    review.rating == Rating::Negative || (review.rating == Rating::Neutral && !r.issues.is_empty())
+}
 
-                let mut out = String::new();
-                for adv in &r.advisories {
-                    if !out.is_empty() {
-                        out.push('\n');
-                    }
-                    out.push_str(&format!("severity: {}\n", adv.severity));
-                    if !adv.ids.is_empty() {
-                        out.push_str("id: ");
-                        out.push_str(&adv.ids.join(", "));
-                        out.push('\n');
-                    }
-                    if !adv.comment.is_empty() {
-                        if !out.is_empty() {
-                            out.push('\n');
-                        }
-                        out.push_str(&adv.comment);
-                    }
+/// Whole-name match, where `*` matches any run of characters and `?` a single one
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.as_bytes(), name.as_bytes());
+    // Position after the last `*`, and the name position it's being tried at
+    let mut backtrack = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                backtrack = Some((p, n));
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, n));
                 }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
 
-                for issue in &r.issues {
-                    out.push_str(&format!("severity: {}\nid: {}\n", issue.severity, issue.id));
-                    if !issue.comment.is_empty() {
-                        if !out.is_empty() {
-                            out.push('\n');
-                        }
-                        out.push_str(&issue.comment);
-                    }
-                }
+/// Explains in debug logs why a review didn't become an audit
+fn log_skipped(r: &Package, reason: impl std::fmt::Display) {
+    log::debug!(
+        "Skipping review of {} {} by {}: {reason} ({:?})",
+        r.package.id.id.name,
+        r.package.id.version,
+        r.common.from.id,
+        PkgVersionReviewId::from(r)
+    );
+}
 
-                if !out.is_empty() {
-                    match notes.as_mut() {
-                        None => { notes = Some(out); },
-                        Some(notes) => {
-                            notes.push('\n');
-                            notes.push_str(&out);
-                        }
-                    }
-                }
+/// Adjustments to the rules for certifying `safe-to-run` and `safe-to-deploy`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScoringPolicy {
+    pub safe_to_run_by_understanding: bool,
+    pub relax_neutral_for_high_trust: bool,
+    /// `Level::Medium` by default, which is also `Level`'s default
+    pub min_understanding_for_deploy: Level,
+}
 
-                audits
-                    .entry(r.package.id.id.name.clone())
-                    .or_insert_with(Vec::new)
-                    .push(vet::AuditEntry {
-                        violation: violation.then(|| format!("={}", r.package.id.version)),
-                        who: vet::StringOrVec::String(author_from_id(pub_id, public_url)),
-                        criteria,
-                        notes: notes.or_else(|| violation.then(|| format!("<https://lib.rs/crates/{}/audit>", r.package.id.id.name))),
-                        aggregated_from: vec![
-                            base_url.clone(),
-                            format!("crev:review/{}", digest.to_base64()),
-                        ],
-                        version,
-                        delta,
-                    });
-                // Candidate for being a better review than the next one
-                last_review = (review.rating > Rating::Neutral
-                    && r.diff_base.is_none()
-                    && r.package.id.version.pre.is_empty())
-                .then_some((review_quality_score, trust, r.package.id.version.clone()));
-            }
-        }
+/// Whether a non-negative review is good enough to be exported at all
+fn meets_min_score(trust: TrustLevel, review: &Review, review_quality_score: u32) -> bool {
+    let min_score = match trust {
+        TrustLevel::Distrust | TrustLevel::None => return false,
+        TrustLevel::Low => level_as_score(Level::High),
+        TrustLevel::Medium => level_as_score(Level::Medium),
+        TrustLevel::High => level_as_score(Level::Low),
+    } + match review.rating {
+        Rating::Negative => level_as_score(Level::None),
+        Rating::Neutral => level_as_score(Level::Medium),
+        Rating::Positive => level_as_score(Level::Low),
+        Rating::Strong => level_as_score(Level::None),
+    };
+    review_quality_score >= min_score
+}
 
-        Ok(vet::AuditsFile {
-            criteria: standard_criteria(),
-            audits,
-        })
-    }
+/// Cuts `notes` to at most `max_len` chars, preferably at the end of a line
+fn truncate_notes(notes: &mut String, max_len: usize, digest: &Digest) {
+    let Some((end, _)) = notes.char_indices().nth(max_len) else {
+        return;
+    };
+    let end = notes[..end].rfind('\n').unwrap_or(end);
+    notes.truncate(end);
+    notes.truncate(notes.trim_end().len());
+    notes.push_str(&format!(
+        "\n… (truncated, see crev review {})",
+        digest.to_base64()
+    ));
+}
 
-    fn vet_version(&self, pkg: &PackageInfo) -> String {
-        if self.include_git_revs && pkg.revision_type == "git" && !pkg.revision.is_empty() {
-            format!("{}@git:{}", pkg.id.version, pkg.revision)
-        } else {
-            pkg.id.version.to_string()
-        }
-    }
+fn is_ignored_violation_author(public_url: Option<&Url>) -> bool {
+    public_url.is_some_and(|u| u.url.contains("MaulingM"))
 }
 
-fn criteria_for_non_negative_review(trust: TrustLevel, r: &Package, review: &Review, review_quality_score: u32) -> Vec<&'static str> {
-    let safe_to_run = trust >= TrustLevel::Medium
+fn criteria_for_non_negative_review(
+    trust: TrustLevel,
+    r: &Package,
+    review: &Review,
+    review_quality_score: u32,
+    policy: ScoringPolicy,
+    [trust_low, trust_medium, trust_high]: [&'static str; 3],
+) -> Vec<&'static str> {
+    let safe_to_run_by_score = trust >= TrustLevel::Medium
         && match review.rating {
             Rating::Negative => false,
+            // Highly trusted reviewers are held to the same bar for neutral reviews as for positive ones
+            Rating::Neutral if policy.relax_neutral_for_high_trust && trust >= TrustLevel::High => {
+                review_quality_score >= level_as_score(Level::Medium) + level_as_score(Level::Low)
+            }
             Rating::Neutral => {
                 review_quality_score
                     >= level_as_score(Level::Medium) + level_as_score(Level::Medium)
@@ -431,8 +2611,13 @@ fn criteria_for_non_negative_review(trust: TrustLevel, r: &Package, review: &Rev
                 review_quality_score >= level_as_score(Level::Low) + level_as_score(Level::Low)
             }
         };
-    let safe_to_deploy = safe_to_run
-        && review.understanding >= Level::Medium
+    let safe_to_run = safe_to_run_by_score
+        || (policy.safe_to_run_by_understanding
+            && trust >= TrustLevel::Medium
+            && review.rating >= Rating::Positive
+            && review.understanding >= Level::Medium);
+    let safe_to_deploy = safe_to_run_by_score
+        && review.understanding >= policy.min_understanding_for_deploy
         && match review.rating {
             Rating::Negative => false,
             Rating::Neutral => review.thoroughness >= Level::High,
@@ -447,9 +2632,9 @@ fn criteria_for_non_negative_review(trust: TrustLevel, r: &Package, review: &Rev
     };
     let trust_criterion = match trust {
         TrustLevel::Distrust | TrustLevel::None => unreachable!(),
-        TrustLevel::Low => "trust-low",
-        TrustLevel::Medium => "trust-medium",
-        TrustLevel::High => "trust-high",
+        TrustLevel::Low => trust_low,
+        TrustLevel::Medium => trust_medium,
+        TrustLevel::High => trust_high,
     };
     let level = if review_quality_score >= level_as_score(Level::High) * 2 {
         "level-high"
@@ -467,23 +2652,201 @@ fn criteria_for_non_negative_review(trust: TrustLevel, r: &Package, review: &Rev
     if safe_to_run {
         criteria.push("safe-to-run");
     }
-    if r.flags.unmaintained {
-        criteria.push("unmaintained");
-    }
+    criteria.extend(criteria_for_flags(&r.flags));
     criteria
 }
 
+/// Custom criteria for every flag crev has. These need matching definitions in `standard_criteria`
+fn criteria_for_flags(flags: &Flags) -> impl Iterator<Item = &'static str> {
+    // Destructured so that new crev flags can't be forgotten here
+    let Flags { unmaintained } = *flags;
+    [unmaintained.then_some("unmaintained")]
+        .into_iter()
+        .flatten()
+}
+
+/// crates.io account of a crev reviewer, for `convert_to_trusted_document`
+#[derive(Debug, Clone)]
+pub struct CratesIoPublisher {
+    /// Numeric id of the crates.io user, as used by cargo-vet
+    pub user_id: u64,
+    /// Crates published by this user that should be trusted
+    pub crates: Vec<String>,
+}
+
 /// Result of `convert_to_repo`
 pub struct RepoInfo {
     pub local_path: PathBuf,
     pub repo_git_url: Option<String>,
     pub repo_https_url: Option<String>,
+    /// Explains why `repo_https_url` is missing
+    pub url_status: UrlStatus,
     pub repo_name: Option<String>,
 }
 
-fn author_from_id(pub_id: &PublicId, verified_url: Option<&Url>) -> String {
+/// Whether the public URL of `audits.toml` could be derived from the proofs repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlStatus {
+    /// `repo_https_url` is set
+    Supported,
+    /// The proofs repo has no `origin` remote
+    NoRemote,
+    /// `origin` is set, but it's not a GitHub or GitLab URL
+    Unsupported,
+}
+
+/// Web URL of a git remote, without the `.git` suffix, e.g. for `git@host:user/repo.git/` or `ssh://git@host:22/user/repo`
+fn https_repo_url(git_url: &str) -> String {
+    let mut u = git_url.trim().to_string();
+    if let Some(rest) = u.strip_prefix("ssh://") {
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+        let host = host.split_once(':').map_or(host, |(host, _port)| host);
+        u = format!("https://{host}/{path}");
+    } else if let Some((host, path)) = u.strip_prefix("git@").and_then(|u| u.split_once(':')) {
+        u = format!("https://{host}/{}", path.trim_start_matches('/'));
+    }
+
+    // Suffixes can be in any order, like `repo.git/` or `repo/.git`
+    loop {
+        let len = u.len();
+        u.truncate(u.trim_end_matches('/').len());
+        if let Some(without_git) = u.strip_suffix(".git") {
+            u.truncate(without_git.len());
+        }
+        if u.len() == len {
+            return u;
+        }
+    }
+}
+
+/// Raw URL of the audits `file_name` and the repo owner's name, for repos on GitHub and GitLab
+fn raw_audits_url(git_url: &str, file_name: &str) -> Option<(String, String)> {
+    let u = https_repo_url(git_url);
+    if let Some(rest) = u.strip_prefix("https://github.com/") {
+        Some((
+            format!("https://raw.githubusercontent.com/{rest}/HEAD/{file_name}"),
+            rest.split('/').next().unwrap_or_default().into(),
+        ))
+    } else {
+        u.strip_prefix("https://gitlab.com/").map(|rest| {
+            (
+                format!("https://gitlab.com/{rest}/-/raw/HEAD/{file_name}"),
+                rest.split('/').next().unwrap_or_default().into(),
+            )
+        })
+    }
+}
+
+fn report_progress(progress: Option<&(dyn Fn(Progress) + Sync)>, done: usize, total: usize) {
+    if let Some(progress) = progress {
+        progress(Progress { done, total });
+    }
+}
+
+/// Serialization errors are kept apart from IO errors, since they're bugs
+/// Audits made by an importer, as a TOML file with a header naming their `source`
+#[cfg(any(
+    feature = "debcargo",
+    feature = "guix",
+    feature = "alpine",
+    feature = "homebrew",
+    feature = "conda",
+    feature = "opensuse",
+    feature = "sbom",
+    feature = "cargo-lock"
+))]
+fn importer_toml(
+    audits: BTreeMap<String, Vec<vet::AuditEntry>>,
+    source: &str,
+) -> Result<String, Error> {
+    let audits = vet::AuditsFile {
+        criteria: Default::default(),
+        audits,
+    };
+    audits.validate()?;
+
+    let mut toml = to_toml(&audits)?;
+
+    toml.insert_str(
+        0,
+        &format!(
+            "# Automatically generated by https://lib.rs/crevette {} from {source}\n\n",
+            env!("CARGO_PKG_VERSION")
+        ),
+    );
+
+    Ok(toml)
+}
+
+fn to_toml(value: &impl serde::Serialize) -> Result<String, Error> {
+    toml_edit::ser::to_string_pretty(value).map_err(|e| Error::TomlSerialization(e.to_string()))
+}
+
+/// Keeps the higher trust of the two sets. Ids trusted by either set aren't distrusted.
+fn merge_trust_set(trusts: &mut TrustSet, other: TrustSet) {
+    for (id, details) in other.trusted {
+        match trusts.trusted.entry(id) {
+            std::collections::hash_map::Entry::Occupied(mut e) => {
+                if details.effective_trust_level > e.get().effective_trust_level {
+                    e.insert(details);
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(e) => {
+                e.insert(details);
+            }
+        }
+    }
+    for (id, details) in other.distrusted {
+        trusts
+            .distrusted
+            .entry(id)
+            .or_default()
+            .reported_by
+            .extend(details.reported_by);
+    }
+    let TrustSet {
+        trusted,
+        distrusted,
+        ..
+    } = trusts;
+    distrusted.retain(|id, _| !trusted.contains_key(id));
+    trusts.traverse_log.extend(other.traverse_log);
+    trusts
+        .trust_ignore_overrides
+        .extend(other.trust_ignore_overrides);
+    trusts
+        .package_review_ignore_override
+        .extend(other.package_review_ignore_override);
+}
+
+/// Verified URL -> Ids, for URLs that more than one trusted Id has
+fn shared_verified_urls(db: &ProofDB, trusts: &TrustSet) -> BTreeMap<String, Vec<Id>> {
+    let mut ids_by_url = BTreeMap::<String, Vec<Id>>::new();
+    for id in trusts.iter_trusted_ids() {
+        if let Some(url) = db.lookup_url(id).verified() {
+            ids_by_url
+                .entry(url.url.clone())
+                .or_default()
+                .push(id.clone());
+        }
+    }
+    ids_by_url.retain(|_, ids| {
+        ids.sort_unstable();
+        ids.len() > 1
+    });
+    ids_by_url
+}
+
+fn author_from_id(pub_id: &PublicId, verified_url: Option<&Url>, who_format: WhoFormat) -> String {
+    let verified_url = match who_format {
+        WhoFormat::NameUrl => verified_url,
+        WhoFormat::CrevIdOnly => return pub_id.id.to_string(),
+        WhoFormat::WebLink => None,
+    };
     if let Some(url) = verified_url.map(|u| u.url.as_str()) {
-        let url = url.strip_suffix("/crev-proofs").unwrap_or(url);
+        let proofs_repo = url.strip_suffix("/crev-proofs");
+        let url = proofs_repo.unwrap_or(url);
         let username = [
             "https://github.com/",
             "https://gitlab.com/",
@@ -491,7 +2854,13 @@ fn author_from_id(pub_id: &PublicId, verified_url: Option<&Url>) -> String {
         ]
         .iter()
         .find_map(|pref| url.strip_prefix(pref))
-        .and_then(|rest| rest.split('/').next());
+        .and_then(|rest| rest.split('/').next())
+        // Any other host, like GitHub Enterprise or self-hosted GitLab, if it's shaped like `https://host/user/crev-proofs`
+        .or_else(|| {
+            let (_host, user) = proofs_repo?.strip_prefix("https://")?.split_once('/')?;
+            let user = user.trim_start_matches('~');
+            (!user.is_empty() && !user.contains('/')).then_some(user)
+        });
         if let Some(username) = username {
             return format!("\"{username}\" ({url})");
         }
@@ -503,7 +2872,460 @@ fn author_from_id(pub_id: &PublicId, verified_url: Option<&Url>) -> String {
         }
         url.to_string()
     } else {
-        format!("https://web.crev.dev/rust-reviews/reviewer/{}", pub_id.id)
+        web_profile_url(&pub_id.id)
+    }
+}
+
+/// Writes a temporary file next to `path` and renames it, so that an interrupted write can't leave a truncated file
+fn write_atomically(path: &std::path::Path, content: &[u8]) -> io::Result<()> {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    let tmp_path = path.with_file_name(file_name);
+
+    fn write_and_rename(
+        tmp_path: &std::path::Path,
+        path: &std::path::Path,
+        content: &[u8],
+    ) -> io::Result<()> {
+        let mut tmp = std::fs::File::create(tmp_path)?;
+        io::Write::write_all(&mut tmp, content)?;
+        tmp.sync_all()?;
+        std::fs::rename(tmp_path, path)
+    }
+
+    let res = write_and_rename(&tmp_path, path, content);
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    res
+}
+
+/// Line of `sha256sum` output, which `sha256sum --check` can verify
+fn sha256sum_line(content: &[u8], file_name: &str) -> String {
+    format!("{}  {file_name}\n", sha256_hex(content))
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    use sha2::Digest as _;
+    sha2::Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Reviewer's page on web.crev.dev, for reviewers without a verified URL
+fn web_profile_url(id: &Id) -> String {
+    format!("https://web.crev.dev/rust-reviews/reviewer/{id}")
+}
+
+/// Where `index_guix::Index::new` keeps its clone of the Guix repo
+#[cfg(feature = "guix")]
+fn guix_checkout_dir(temp_dir_path: &std::path::Path) -> std::path::PathBuf {
+    temp_dir_path.join("guix")
+}
+
+#[cfg(feature = "guix")]
+fn is_selected_guix_category(category: &str, categories: Option<&[&str]>) -> bool {
+    categories.map_or(true, |categories| categories.contains(&category))
+}
+
+/// Maintainer and alphabetically sorted uploaders of a Debian package, without duplicates that differ only in case or whitespace
+#[cfg(feature = "debcargo")]
+fn debian_who(
+    maintainer_name: Option<String>,
+    maintainer_email: Option<String>,
+    uploaders: &[String],
+) -> Vec<String> {
+    let normalized = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let mut who = vec![];
+    let mut seen = HashSet::new();
+    let mut sorted_uploaders = vec![];
+    if let Some(email) = maintainer_email {
+        who.push(format!(
+            "\"{}\" <{email}>",
+            maintainer_name.as_deref().unwrap_or_default()
+        ));
+        seen.insert(normalized(&email));
+        if let Some(name) = maintainer_name {
+            seen.insert(normalized(&name));
+        }
+    }
+    for a in uploaders {
+        let a = cargo_author::Author::new(a);
+        if let Some(email) = a.email {
+            let uploader = format!("\"{}\" <{email}>", a.name.as_deref().unwrap_or_default());
+            if let Some(name) = a.name {
+                if !seen.insert(normalized(&name)) {
+                    continue;
+                }
+            }
+            if !seen.insert(normalized(&email)) {
+                continue;
+            }
+            sorted_uploaders.push(uploader);
+        }
+    }
+    // Debian doesn't keep uploaders in any particular order
+    sorted_uploaders.sort_by_cached_key(|u| normalized(u));
+    who.extend(sorted_uploaders);
+    who
+}
+
+/// Debian suites accepted by `from_debcargo_repo`
+#[cfg(feature = "debcargo")]
+pub const DEBIAN_SUITES: [&str; 6] = [
+    "oldstable",
+    "stable",
+    "testing",
+    "unstable",
+    "sid",
+    "experimental",
+];
+
+/// Where and how `from_debcargo_repo` gets the list of Debian packages
+#[cfg(feature = "debcargo")]
+#[derive(Debug, Clone)]
+pub struct DebianOptions {
+    /// One of [`DEBIAN_SUITES`]
+    pub suite: String,
+    /// Debian archive like `http://mirror.internal/debian`. `deb.debian.org` is used when it's `None`.
+    pub mirror: Option<String>,
+    /// Use only the previously downloaded package list
+    pub offline: bool,
+    /// Cached package list older than this is deleted and downloaded again in full, even if the server says it's unchanged
+    pub max_cache_age: Option<std::time::Duration>,
+    pub http: HttpConfig,
+}
+
+#[cfg(feature = "debcargo")]
+impl Default for DebianOptions {
+    fn default() -> Self {
+        Self {
+            suite: "stable".into(),
+            mirror: None,
+            offline: false,
+            max_cache_age: None,
+            http: HttpConfig::default(),
+        }
+    }
+}
+
+/// How `from_opensuse_repo` gets the list of openSUSE packages
+#[cfg(feature = "opensuse")]
+#[derive(Debug, Clone, Default)]
+pub struct OpensuseOptions {
+    /// Use only the previously downloaded package list
+    pub offline: bool,
+    /// Cached package list older than this is deleted and downloaded again in full, even if the server says it's unchanged
+    pub max_cache_age: Option<std::time::Duration>,
+    pub http: HttpConfig,
+}
+
+#[cfg(feature = "debcargo")]
+fn debian_sources_url(suite: &str, mirror: Option<&str>) -> Result<String, Error> {
+    if !DEBIAN_SUITES.contains(&suite) {
+        return Err(Error::IO(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Unknown Debian suite '{suite}'. Use one of: {}",
+                DEBIAN_SUITES.join(", ")
+            ),
+        )));
+    }
+    let mirror = mirror.map_or("https://deb.debian.org/debian", |m| m.trim_end_matches('/'));
+    Ok(format!("{mirror}/dists/{suite}/main/source/Sources.gz"))
+}
+
+/// Network settings for importers that download package lists
+#[cfg(any(feature = "debcargo", feature = "aggregate", feature = "opensuse"))]
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Proxy URL for all requests. `HTTPS_PROXY` and other proxy env vars are used when it's `None`.
+    pub proxy: Option<String>,
+    /// Limit for a whole request, including the download
+    pub timeout: std::time::Duration,
+}
+
+#[cfg(any(feature = "debcargo", feature = "aggregate", feature = "opensuse"))]
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            timeout: std::time::Duration::from_secs(60),
+        }
+    }
+}
+
+#[cfg(any(feature = "debcargo", feature = "aggregate", feature = "opensuse"))]
+impl HttpConfig {
+    fn client(&self) -> Result<reqwest::blocking::Client, Error> {
+        let http_err = |e: reqwest::Error| {
+            Error::IO(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Can't set up HTTP client: {e}"),
+            ))
+        };
+        let mut builder = reqwest::blocking::Client::builder().timeout(self.timeout);
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(http_err)?);
+        }
+        builder.build().map_err(http_err)
+    }
+}
+
+/// Deletes `file` if it was modified more than `max_age` before `now`. A missing file is fine.
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+fn remove_stale_cache(
+    file: &std::path::Path,
+    max_age: Option<std::time::Duration>,
+    now: std::time::SystemTime,
+) -> io::Result<()> {
+    let Some(max_age) = max_age else {
+        return Ok(());
+    };
+    let modified = match std::fs::metadata(file) {
+        Ok(meta) => meta.modified()?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    // Modification times in the future aren't stale
+    if now.duration_since(modified).is_ok_and(|age| age > max_age) {
+        log::debug!("Removing {} older than {max_age:?}", file.display());
+        std::fs::remove_file(file)?;
+    }
+    Ok(())
+}
+
+/// Keeps `file` up to date, using a conditional GET with validators stored in a `.validators` sidecar file.
+/// Retries server errors and network failures.
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+fn download_if_modified(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    file: &std::path::Path,
+) -> Result<(), Error> {
+    use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+
+    let sibling = |suffix: &str| {
+        let mut file_name = file.file_name().unwrap_or_default().to_os_string();
+        file_name.push(suffix);
+        file.with_file_name(file_name)
+    };
+    let validators_file = sibling(".validators");
+    let file_tmp = sibling(".tmp");
+
+    let dl_err = |e: String| {
+        Error::IO(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Can't download {url}: {e}"),
+        ))
+    };
+
+    // First line is the ETag, second is Last-Modified. Either may be empty.
+    let cached_validators = if file.exists() {
+        std::fs::read_to_string(&validators_file).ok()
+    } else {
+        None
+    };
+    let mut cached_validators = cached_validators.iter().flat_map(|v| v.lines());
+    let etag = cached_validators.next().filter(|v| !v.is_empty());
+    let last_modified = cached_validators.next().filter(|v| !v.is_empty());
+
+    let validators = retry_with_backoff(
+        DOWNLOAD_ATTEMPTS,
+        std::time::Duration::from_secs(1),
+        std::thread::sleep,
+        || {
+            let mut request = client.get(url);
+            if let Some(etag) = etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+
+            let retryable = |e: reqwest::Error| AttemptError {
+                error: dl_err(e.to_string()),
+                retryable: true,
+                retry_after: None,
+            };
+            let mut response = request.send().map_err(retryable)?;
+            let status = response.status();
+            if status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(None);
+            }
+            if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok()?.parse().ok())
+                    .map(std::time::Duration::from_secs);
+                return Err(AttemptError {
+                    error: dl_err(format!("HTTP status {status}")),
+                    retryable: true,
+                    retry_after,
+                });
+            }
+            if !status.is_success() {
+                return Err(AttemptError {
+                    error: dl_err(format!("HTTP status {status}")),
+                    retryable: false,
+                    retry_after: None,
+                });
+            }
+            let header = |name: reqwest::header::HeaderName| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let validators = format!("{}\n{}\n", header(ETAG), header(LAST_MODIFIED));
+
+            let mut out = std::fs::File::create(&file_tmp).map_err(|e| AttemptError {
+                error: e.into(),
+                retryable: false,
+                retry_after: None,
+            })?;
+            response.copy_to(&mut out).map_err(retryable)?;
+            Ok(Some(validators))
+        },
+    )?;
+
+    if let Some(validators) = validators {
+        std::fs::rename(&file_tmp, file)?;
+        std::fs::write(&validators_file, validators)?;
+    }
+    Ok(())
+}
+
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Longest wait between attempts, even if the server asks for more
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+const MAX_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+struct AttemptError {
+    error: Error,
+    retryable: bool,
+    /// Delay requested by the server
+    retry_after: Option<std::time::Duration>,
+}
+
+/// Calls `attempt` until it succeeds, sleeping twice as long after every failure
+#[cfg(any(feature = "debcargo", feature = "opensuse"))]
+fn retry_with_backoff<T>(
+    max_attempts: u32,
+    mut delay: std::time::Duration,
+    sleep: impl Fn(std::time::Duration),
+    mut attempt: impl FnMut() -> Result<T, AttemptError>,
+) -> Result<T, Error> {
+    let mut attempts_left = max_attempts;
+    loop {
+        let e = match attempt() {
+            Ok(res) => return Ok(res),
+            Err(e) => e,
+        };
+        attempts_left = attempts_left.saturating_sub(1);
+        if attempts_left == 0 || !e.retryable {
+            return Err(e.error);
+        }
+        sleep(e.retry_after.unwrap_or(delay).min(MAX_RETRY_DELAY));
+        delay *= 2;
+    }
+}
+
+#[cfg(any(
+    feature = "debcargo",
+    feature = "guix",
+    feature = "alpine",
+    feature = "homebrew",
+    feature = "conda",
+    feature = "opensuse"
+))]
+fn missing_in_offline_mode(path: &std::path::Path) -> Error {
+    Error::IO(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!(
+            "{} is missing, and can't be downloaded in offline mode",
+            path.display()
+        ),
+    ))
+}
+
+/// Words used by advisory databases if `advisory_names` is set, otherwise crev's levels
+fn severity_name(severity: Level, advisory_names: bool) -> &'static str {
+    match (severity, advisory_names) {
+        (Level::None, false) => "none",
+        (Level::None, true) => "informational",
+        (Level::Low, _) => "low",
+        (Level::Medium, false) => "medium",
+        (Level::Medium, true) => "moderate",
+        (Level::High, false) => "high",
+        (Level::High, true) => "critical",
+    }
+}
+
+/// Number of advisories per severity, from the most severe, e.g. "3 advisories: 2 high, 1 medium"
+fn severity_summary(
+    severities: impl Iterator<Item = Level>,
+    advisory_names: bool,
+) -> Option<String> {
+    let mut counts = BTreeMap::new();
+    for severity in severities {
+        *counts.entry(std::cmp::Reverse(severity)).or_insert(0) += 1;
+    }
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return None;
+    }
+    let breakdown = counts
+        .into_iter()
+        .map(|(std::cmp::Reverse(severity), n)| {
+            format!("{n} {}", severity_name(severity, advisory_names))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!(
+        "{total} {}: {breakdown}",
+        if total == 1 { "advisory" } else { "advisories" }
+    ))
+}
+
+/// Appends a link to the advisory database for well-known advisory ids
+fn advisory_id_with_url(id: &str) -> String {
+    let id = id.trim();
+    match advisory_url(id) {
+        Some(url) => format!("{id} <{url}>"),
+        None => id.to_string(),
+    }
+}
+
+/// Trimmed ids, without blank ones
+fn non_empty_ids<'a>(ids: impl IntoIterator<Item = &'a String>) -> Vec<&'a str> {
+    ids.into_iter()
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+fn advisory_url(id: &str) -> Option<String> {
+    if id.starts_with("RUSTSEC-") {
+        Some(format!("https://rustsec.org/advisories/{id}.html"))
+    } else if id.starts_with("CVE-") {
+        Some(format!("https://nvd.nist.gov/vuln/detail/{id}"))
+    } else if id.starts_with("GHSA-") {
+        Some(format!("https://github.com/advisories/{id}"))
+    } else {
+        None
     }
 }
 
@@ -516,63 +3338,100 @@ fn level_as_score(level: Level) -> u32 {
     }
 }
 
-fn standard_criteria() -> BTreeMap<&'static str, vet::CriteriaEntry> {
+/// Page listing reviews of a crate, linked from violations without notes
+pub const DEFAULT_AUDIT_PORTAL_URL_TEMPLATE: &str = "https://lib.rs/crates/{name}/audit";
+
+/// Names of criteria for low, medium, and high trust in reviewers, used by default
+pub const DEFAULT_TRUST_CRITERIA_NAMES: [&str; 3] = ["trust-low", "trust-medium", "trust-high"];
+
+/// How crevette assigns its standard criteria, for `verbose_criteria_descriptions`
+fn criterion_derivation(
+    name: &str,
+    [trust_low, trust_medium, trust_high]: [&str; 3],
+) -> Option<String> {
+    let score = |level| level_as_score(level) * 2;
+    let scores = "(none=0, low=1, medium=3, high=7)";
+    Some(match name {
+        "level-high" => format!("Derived from crev thoroughness+understanding >= {} {scores}", score(Level::High)),
+        "level-medium" => format!("Derived from crev thoroughness+understanding >= {} {scores}", score(Level::Medium)),
+        "level-low" => format!("Derived from crev thoroughness+understanding >= {} {scores}", score(Level::Low)),
+        "level-none" => format!("Derived from crev thoroughness+understanding < {} {scores}", score(Level::Low)),
+        "strong" | "positive" | "neutral" => format!("Derived from crev review rating {name}"),
+        "unmaintained" | "maintained" => "Derived from crev's unmaintained flag".into(),
+        n if n == trust_high => "Derived from the reviewer's effective trust level high in the publisher's web of trust".into(),
+        n if n == trust_medium => "Derived from the reviewer's effective trust level medium in the publisher's web of trust".into(),
+        n if n == trust_low => "Derived from the reviewer's effective trust level low in the publisher's web of trust".into(),
+        _ => return None,
+    })
+}
+
+fn standard_criteria(
+    [trust_low, trust_medium, trust_high]: [&'static str; 3],
+) -> BTreeMap<&'static str, vet::CriteriaEntry> {
     let crev_criteria_url = vec!["https://github.com/crev-dev".into()];
     [
-        ("trust-high", vet::CriteriaEntry {
-            description: Some("Author of this review is well known and trusted by the publisher of this audit repository. This means 'at least this much', so higher levels imply all lower levels"),
-            implies: vec!["trust-medium"],
+        (trust_high, vet::CriteriaEntry {
+            description: Some("Author of this review is well known and trusted by the publisher of this audit repository. This means 'at least this much', so higher levels imply all lower levels".into()),
+            implies: vec![trust_medium],
             aggregated_from: crev_criteria_url.clone(),
         }),
-        ("trust-medium", vet::CriteriaEntry {
-            description: Some("Author of this review is somewhat known and trusted by the publisher of this audit repository"),
-            implies: vec!["trust-low"],
+        (trust_medium, vet::CriteriaEntry {
+            description: Some("Author of this review is somewhat known and trusted by the publisher of this audit repository".into()),
+            implies: vec![trust_low],
             aggregated_from: crev_criteria_url.clone(),
         }),
-        ("trust-low", vet::CriteriaEntry {
-            description: Some("Author of this review is not well known, or not trusted much, by the publisher of this audit repository"),
+        (trust_low, vet::CriteriaEntry {
+            description: Some("Author of this review is not well known, or not trusted much, by the publisher of this audit repository".into()),
             implies: vec![],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("strong", vet::CriteriaEntry {
-            description: Some("Strong endorsement. It implies a positive rating"),
+            description: Some("Strong endorsement. It implies a positive rating".into()),
             implies: vec!["positive"],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("positive", vet::CriteriaEntry {
-            description: Some("Positive review rating"),
+            description: Some("Positive review rating".into()),
             implies: vec![],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("neutral", vet::CriteriaEntry {
-            description: Some("There is no rating either way. Check the comments for reports of issues"),
+            description: Some("There is no rating either way. Check the comments for reports of issues".into()),
             implies: vec![],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("level-high", vet::CriteriaEntry {
-            description: Some("The code has been thoroughly reviewed and/or with high understanding. This means 'at least this much' so higher levels imply all lower levels"),
+            description: Some("The code has been thoroughly reviewed and/or with high understanding. This means 'at least this much' so higher levels imply all lower levels".into()),
             implies: vec!["level-medium"],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("level-medium", vet::CriteriaEntry {
-            description: Some("The code has been reviewed with average thoroughness or understanding. This means 'at least this much' so higher levels imply all lower levels"),
+            description: Some("The code has been reviewed with average thoroughness or understanding. This means 'at least this much' so higher levels imply all lower levels".into()),
             implies: vec!["level-low"],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("level-low", vet::CriteriaEntry {
-            description: Some("The code has been only checked at a glance and/or with low understanding. This means 'at least this much' so higher levels imply all lower levels"),
+            description: Some("The code has been only checked at a glance and/or with low understanding. This means 'at least this much' so higher levels imply all lower levels".into()),
             implies: vec!["level-none"],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("level-none", vet::CriteriaEntry {
-            description: Some("The code hasn't been reviewed or hasn't been understood"),
+            description: Some("The code hasn't been reviewed or hasn't been understood".into()),
             implies: vec![],
             aggregated_from: crev_criteria_url.clone(),
         }),
         ("unmaintained", vet::CriteriaEntry {
-            description: Some("The package has been flagged as unmaintained"),
+            description: Some("The package has been flagged as unmaintained".into()),
+            implies: vec![],
+            aggregated_from: crev_criteria_url.clone(),
+        }),
+        ("maintained", vet::CriteriaEntry {
+            description: Some("The package hasn't been flagged as unmaintained. It's never certified, only violated by reviews flagging the package as unmaintained".into()),
             implies: vec![],
             aggregated_from: crev_criteria_url.clone(),
         }),
     ].into_iter().collect()
 }
+
+#[cfg(test)]
+mod tests;
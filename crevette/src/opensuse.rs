@@ -0,0 +1,77 @@
+//! Minimal reader of openSUSE Tumbleweed's source repository metadata
+//!
+//! Crates packaged on their own are `rust-<crate>` source packages, built in OBS from `openSUSE:Factory`.
+
+use std::io;
+
+pub const SOURCE_REPO_URL: &str = "https://download.opensuse.org/tumbleweed/repo/src-oss";
+pub const OBS_PROJECT: &str = "openSUSE:Factory";
+
+pub struct Crate {
+    pub name: String,
+    pub version: String,
+    /// `rust-foo`
+    pub package: String,
+}
+
+/// URL of the package in the Open Build Service
+pub fn obs_package_url(package: &str) -> String {
+    format!("https://build.opensuse.org/package/show/{OBS_PROJECT}/{package}")
+}
+
+/// URL of the gzipped list of source packages, which changes with every update of the repository
+pub fn primary_url(client: &reqwest::blocking::Client) -> io::Result<String> {
+    let url = format!("{SOURCE_REPO_URL}/repodata/repomd.xml");
+    let repomd = client
+        .get(&url)
+        .send()
+        .and_then(|res| res.error_for_status())
+        .and_then(|res| res.text())
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Can't download {url}: {e}")))?;
+    let href = primary_href(&repomd).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "repomd.xml doesn't list primary metadata",
+        )
+    })?;
+    Ok(format!("{SOURCE_REPO_URL}/{href}"))
+}
+
+/// `href` of the `primary` data in `repomd.xml`
+fn primary_href(repomd: &str) -> Option<&str> {
+    let data = repomd
+        .split("<data ")
+        .find(|d| d.starts_with("type=\"primary\""))?;
+    attribute(data.split("<location ").nth(1)?, "href")
+}
+
+/// Crates in `primary.xml` of the source repository
+pub fn list_all(primary: &str) -> Vec<Crate> {
+    primary
+        .split("<package ")
+        .skip(1)
+        .filter_map(|p| {
+            let package = element_text(p, "name")?;
+            let name = package.strip_prefix("rust-")?;
+            // RPM uses `~` for pre-releases
+            let version = attribute(p.split("<version ").nth(1)?, "ver")?.replace('~', "-");
+            // Packages of older versions have the version in the name, like `rust-foo0.1`
+            semver::Version::parse(&version).ok()?;
+            Some(Crate {
+                name: name.to_string(),
+                version,
+                package: package.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn element_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = xml.split_once(&format!("<{name}>"))?;
+    Some(rest.split_once(&format!("</{name}>"))?.0.trim())
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = tag.split_once(&format!("{name}=\""))?;
+    Some(rest.split_once('"')?.0)
+}
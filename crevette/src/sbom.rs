@@ -0,0 +1,128 @@
+//! Crates listed in JSON SBOMs, in CycloneDX or SPDX format
+
+use crate::SbomFormat;
+
+pub struct Crate {
+    pub name: String,
+    pub version: String,
+    /// Organization or person supplying the component, if the SBOM says
+    pub supplier: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct CycloneDx {
+    #[serde(default)]
+    components: Vec<CycloneDxComponent>,
+}
+
+#[derive(serde::Deserialize)]
+struct CycloneDxComponent {
+    purl: Option<String>,
+    supplier: Option<CycloneDxSupplier>,
+    /// Components can be nested, e.g. dependencies of an application
+    #[serde(default)]
+    components: Vec<CycloneDxComponent>,
+}
+
+#[derive(serde::Deserialize)]
+struct CycloneDxSupplier {
+    name: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Spdx {
+    #[serde(default)]
+    packages: Vec<SpdxPackage>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    /// Like `Organization: Name (email)`, or `NOASSERTION`
+    supplier: Option<String>,
+    #[serde(default)]
+    external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxExternalRef {
+    reference_type: String,
+    reference_locator: String,
+}
+
+/// Crates.io crates in the SBOM. Components that aren't crates, or are from other registries, are skipped.
+pub fn list_all(json: &str, format: SbomFormat) -> Result<Vec<Crate>, serde_json::Error> {
+    let mut all = Vec::new();
+    match format {
+        SbomFormat::CycloneDx => {
+            let bom: CycloneDx = serde_json::from_str(json)?;
+            let mut components: Vec<_> = bom.components.iter().collect();
+            while let Some(c) = components.pop() {
+                components.extend(&c.components);
+                let Some((name, version)) = c.purl.as_deref().and_then(crate_from_purl) else {
+                    continue;
+                };
+                let supplier = c
+                    .supplier
+                    .as_ref()
+                    .and_then(|s| s.name.as_deref())
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty());
+                all.push(Crate {
+                    name,
+                    version,
+                    supplier: supplier.map(String::from),
+                });
+            }
+        }
+        SbomFormat::Spdx => {
+            let doc: Spdx = serde_json::from_str(json)?;
+            for p in doc.packages {
+                let Some((name, version)) = p
+                    .external_refs
+                    .iter()
+                    .filter(|r| r.reference_type == "purl")
+                    .find_map(|r| crate_from_purl(&r.reference_locator))
+                else {
+                    continue;
+                };
+                all.push(Crate {
+                    name,
+                    version,
+                    supplier: p.supplier.as_deref().and_then(spdx_supplier_name),
+                });
+            }
+        }
+    }
+    Ok(all)
+}
+
+/// Name and version from `pkg:cargo/name@version`
+pub fn crate_from_purl(purl: &str) -> Option<(String, String)> {
+    let rest = purl.trim().strip_prefix("pkg:cargo/")?;
+    let rest = rest.split_once('#').map_or(rest, |(rest, _subpath)| rest);
+    let (rest, qualifiers) = rest.split_once('?').unwrap_or((rest, ""));
+    // Audits are for crates.io, and purls of other registries have their URL
+    if qualifiers
+        .split('&')
+        .any(|q| q.starts_with("repository_url="))
+    {
+        return None;
+    }
+    let (name, version) = rest.rsplit_once('@')?;
+    // purl encodes `+` of build metadata
+    let version = version.replace("%2B", "+").replace("%2b", "+");
+    semver::Version::parse(&version).ok()?;
+    (!name.is_empty()).then(|| (name.to_string(), version))
+}
+
+/// Name from SPDX's `Organization: Name (email)` or `Person: Name`
+fn spdx_supplier_name(supplier: &str) -> Option<String> {
+    let name = supplier.split_once(':').map_or(supplier, |(_, name)| name);
+    let name = name
+        .split_once('(')
+        .map_or(name, |(name, _email)| name)
+        .trim();
+    (!name.is_empty() && name != "NOASSERTION").then(|| name.to_string())
+}
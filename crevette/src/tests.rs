@@ -0,0 +1,2397 @@
+use super::*;
+use crev_data::proof::{self, ContentExt};
+use crev_data::{UnlockedId, Version};
+use crev_wot::FetchSource;
+
+fn package_info(name: &str, version: &str) -> PackageInfo {
+    PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE_CRATES_IO.into(),
+            name.into(),
+            Version::parse(version).unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: String::new(),
+        revision_type: proof::default_revision_type(),
+    }
+}
+
+fn good_review() -> Review {
+    Review {
+        thoroughness: Level::High,
+        understanding: Level::High,
+        rating: Rating::Positive,
+    }
+}
+
+fn review_proof(
+    from: &UnlockedId,
+    name: &str,
+    version: &str,
+    review: Review,
+    edit: impl FnOnce(&mut Package),
+) -> proof::Proof {
+    let mut package = from
+        .as_public_id()
+        .create_package_review_proof(package_info(name, version), review, vec![], String::new())
+        .unwrap();
+    edit(&mut package);
+    package.sign_by(from).unwrap()
+}
+
+fn crevette_for(id: &UnlockedId, proofs: Vec<proof::Proof>) -> Crevette {
+    let mut db = ProofDB::new();
+    db.import_from_iter(proofs.into_iter().map(|p| (p, FetchSource::LocalUser)));
+    Crevette::new_with_options(
+        db,
+        id.as_ref(),
+        &TrustDistanceParams::default(),
+        TrustLevel::Low,
+    )
+    .unwrap()
+}
+
+/// Directory in the system's temp dir, removed when dropped, even if the test fails
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        // Tests run in parallel threads of the same process
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("crevette-{name}-{}-{n}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn alice() -> UnlockedId {
+    UnlockedId::generate_for_git_url("https://github.com/alice/crev-proofs")
+}
+
+/// Crevette of `alice()` with just her one review
+fn alice_crevette(
+    name: &str,
+    version: &str,
+    review: Review,
+    edit: impl FnOnce(&mut Package),
+) -> Crevette {
+    let a = alice();
+    crevette_for(&a, vec![review_proof(&a, name, version, review, edit)])
+}
+
+fn only_entry(crevette: &Crevette, name: &str) -> vet::AuditEntry {
+    let mut doc = crevette.convert_to_document().unwrap();
+    let mut entries = doc.audits.remove(name).unwrap();
+    assert_eq!(1, entries.len());
+    entries.remove(0)
+}
+
+fn who_string(entry: &vet::AuditEntry) -> &str {
+    match &entry.who {
+        vet::StringOrVec::String(s) => s,
+        vet::StringOrVec::Vec(_) => panic!("expected a single reviewer"),
+    }
+}
+
+#[test]
+fn who_format_name_url() {
+    let crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let entry = only_entry(&crevette, "foo");
+    assert_eq!("\"alice\" (https://github.com/alice)", who_string(&entry));
+}
+
+#[test]
+fn provenance_of_unverified_reviewer() {
+    let a = alice();
+    let mut db = ProofDB::new();
+    let mirror = Url::new_git("https://example.com/mirror/crev-proofs");
+    db.import_from_iter(std::iter::once((
+        review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+        FetchSource::Url(std::sync::Arc::new(mirror)),
+    )));
+    let crevette = Crevette::new_with_options(
+        db,
+        a.as_ref(),
+        &TrustDistanceParams::default(),
+        TrustLevel::Low,
+    )
+    .unwrap();
+    let entry = only_entry(&crevette, "foo");
+    let profile = format!(
+        "https://web.crev.dev/rust-reviews/reviewer/{}",
+        a.as_public_id().id
+    );
+    assert_eq!(profile, who_string(&entry));
+    assert_eq!(profile, entry.aggregated_from[0]);
+}
+
+#[test]
+fn who_format_crev_id_only() {
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![review_proof(&a, "foo", "1.0.0", good_review(), |_| {})],
+    );
+    crevette.set_who_format(WhoFormat::CrevIdOnly);
+    let entry = only_entry(&crevette, "foo");
+    assert_eq!(a.as_public_id().id.to_string(), who_string(&entry));
+}
+
+#[test]
+fn who_format_web_link() {
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![review_proof(&a, "foo", "1.0.0", good_review(), |_| {})],
+    );
+    crevette.set_who_format(WhoFormat::WebLink);
+    let entry = only_entry(&crevette, "foo");
+    assert_eq!(
+        format!(
+            "https://web.crev.dev/rust-reviews/reviewer/{}",
+            a.as_public_id().id
+        ),
+        who_string(&entry)
+    );
+}
+
+#[test]
+fn alternatives_in_violation_notes() {
+    let crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        r.alternatives.insert(proof::PackageId {
+            source: SOURCE_CRATES_IO.into(),
+            name: "bar".into(),
+        });
+    });
+    let entry = only_entry(&crevette, "foo");
+    assert!(entry.violation.is_some());
+    assert!(entry.notes.unwrap().contains("Alternative suggested: bar"));
+}
+
+#[test]
+fn flags_become_criteria() {
+    let crevette = alice_crevette("foo", "1.0.0", good_review(), |r| {
+        r.flags.unmaintained = true
+    });
+    let entry = only_entry(&crevette, "foo");
+    assert!(entry.criteria.contains(&"unmaintained"));
+    assert!(standard_criteria(DEFAULT_TRUST_CRITERIA_NAMES).contains_key("unmaintained"));
+}
+
+#[test]
+fn rustsec_ids_are_linked() {
+    let crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        r.advisories.push(crev_data::review::Advisory {
+            ids: vec!["RUSTSEC-2021-0001".into()],
+            severity: Level::High,
+            ..Default::default()
+        });
+    });
+    let entry = only_entry(&crevette, "foo");
+    assert!(entry
+        .notes
+        .unwrap()
+        .contains("https://rustsec.org/advisories/RUSTSEC-2021-0001.html"));
+}
+
+#[test]
+fn criteria_are_sorted() {
+    let a = alice();
+    let proofs = vec![
+        review_proof(&a, "foo", "1.0.0", good_review(), |r| {
+            r.flags.unmaintained = true
+        }),
+        review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+    ];
+    let crevette = crevette_for(&a, proofs);
+    for name in ["foo", "bar"] {
+        let entry = only_entry(&crevette, name);
+        let mut sorted = entry.criteria.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, entry.criteria);
+    }
+}
+
+#[test]
+fn audit_entries_match_document() {
+    let a = alice();
+    let proofs = vec![
+        review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+        review_proof(&a, "foo", "1.1.0", good_review(), |_| {}),
+        review_proof(&a, "bar", "1.0.0", Review::new_negative(), |_| {}),
+    ];
+    let crevette = crevette_for(&a, proofs);
+    let doc = crevette.convert_to_document().unwrap();
+    let in_doc: usize = doc.audits.values().map(|a| a.len()).sum();
+    assert_eq!(in_doc, crevette.audit_entries().count());
+}
+
+#[cfg(feature = "debcargo")]
+#[test]
+fn debian_suite_urls() {
+    assert_eq!(
+        "https://deb.debian.org/debian/dists/sid/main/source/Sources.gz",
+        debian_sources_url("sid", None).unwrap()
+    );
+    assert!(debian_sources_url("bananas", None).is_err());
+    assert_eq!(
+        "http://mirror.internal/debian/dists/stable/main/source/Sources.gz",
+        debian_sources_url("stable", Some("http://mirror.internal/debian/")).unwrap()
+    );
+}
+
+#[test]
+fn json_matches_document() {
+    let crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let json: serde_json::Value =
+        serde_json::from_str(&crevette.convert_to_json().unwrap()).unwrap();
+    assert_eq!(
+        serde_json::to_value(crevette.convert_to_document().unwrap()).unwrap(),
+        json
+    );
+    assert!(json["audits"]["foo"][0]["aggregated-from"].is_array());
+}
+
+#[test]
+fn duplicate_advisory_and_issue_noted_once() {
+    let crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        r.advisories.push(crev_data::review::Advisory {
+            ids: vec!["RUSTSEC-2021-0001".into()],
+            severity: Level::High,
+            comment: "Use after free".into(),
+            ..Default::default()
+        });
+        let mut issue = crev_data::review::Issue::new("RUSTSEC-2021-0001".into());
+        issue.severity = Level::High;
+        issue.comment = "Use after free".into();
+        r.issues.push(issue);
+    });
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert_eq!(1, notes.matches("Use after free").count());
+}
+
+#[test]
+fn unmaintained_as_violation() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |r| {
+        r.flags.unmaintained = true
+    });
+    crevette.set_unmaintained_as_violation(true);
+    let doc = crevette.convert_to_document().unwrap();
+    let entries = &doc.audits["foo"];
+    assert_eq!(2, entries.len());
+    assert!(entries
+        .iter()
+        .any(|e| e.violation.as_deref() == Some("=1.0.0") && e.criteria == ["maintained"]));
+    assert!(entries
+        .iter()
+        .any(|e| e.violation.is_none() && e.criteria.contains(&"unmaintained")));
+}
+
+#[test]
+fn unmaintained_violation_of_ignored_author() {
+    let m = UnlockedId::generate_for_git_url("https://github.com/MaulingMonkey/crev-proofs");
+    let proof = review_proof(&m, "foo", "1.0.0", good_review(), |r| {
+        r.flags.unmaintained = true
+    });
+    let mut crevette = crevette_for(&m, vec![proof]);
+    crevette.set_unmaintained_as_violation(true);
+    assert!(only_entry(&crevette, "foo").violation.is_none());
+}
+
+#[test]
+fn scored_reviews_expose_selection() {
+    let a = alice();
+    let proofs = vec![
+        review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+        review_proof(&a, "foo", "2.0.0", good_review(), |_| {}),
+    ];
+    let crevette = crevette_for(&a, proofs);
+    let scored = crevette.scored_reviews();
+    let best = scored["foo"][0];
+    assert_eq!(Version::new(2, 0, 0), best.review.package.id.version);
+    assert_eq!(TrustLevel::High, best.trust);
+    assert_eq!(level_as_score(Level::High) * 2, best.review_quality_score);
+    assert_eq!(
+        Some(TrustLevel::High),
+        crevette.trust_for_review(&PkgVersionReviewId::from(best.review))
+    );
+}
+
+#[test]
+fn validation_rejects_what_cargo_vet_would() {
+    let entry = |version: Option<&str>, criteria| vet::AuditEntry {
+        who: vet::StringOrVec::String("alice".into()),
+        violation: None,
+        criteria,
+        version: version.map(String::from),
+        delta: None,
+        notes: None,
+        aggregated_from: vec![],
+    };
+    let doc = |entry| vet::AuditsFile {
+        audits: [("foo".to_string(), vec![entry])].into_iter().collect(),
+        criteria: standard_criteria(DEFAULT_TRUST_CRITERIA_NAMES),
+    };
+    assert!(doc(entry(Some("1.0.0"), vec!["safe-to-run", "trust-high"]))
+        .validate()
+        .is_ok());
+    assert!(doc(entry(None, vec!["safe-to-run"])).validate().is_err());
+    assert!(doc(entry(Some("1.0.0"), vec!["bananas"]))
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn diff_of_audits_files() {
+    let entry = |who: &str, version: &str, criteria| vet::AuditEntry {
+        who: vet::StringOrVec::String(who.into()),
+        violation: None,
+        criteria,
+        version: Some(version.into()),
+        delta: None,
+        notes: None,
+        aggregated_from: vec![],
+    };
+    let old = vet::AuditsFile {
+        audits: [
+            (
+                "foo".to_string(),
+                vec![
+                    entry("alice", "1.0.0", vec!["safe-to-run"]),
+                    entry("bob", "1.0.0", vec!["safe-to-run"]),
+                ],
+            ),
+            (
+                "bar".to_string(),
+                vec![entry("alice", "0.1.0", vec!["safe-to-deploy"])],
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        criteria: standard_criteria(DEFAULT_TRUST_CRITERIA_NAMES),
+    };
+    let new = vet::AuditsFile {
+        audits: [
+            // Reordered, and one upgraded
+            (
+                "foo".to_string(),
+                vec![
+                    entry("bob", "1.0.0", vec!["safe-to-run"]),
+                    entry("alice", "1.0.0", vec!["safe-to-deploy"]),
+                ],
+            ),
+            (
+                "bar".to_string(),
+                vec![entry("alice", "0.1.0", vec!["safe-to-deploy"])],
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        criteria: standard_criteria(DEFAULT_TRUST_CRITERIA_NAMES),
+    };
+
+    assert!(old.diff(&old).is_empty());
+    let diff = old.diff(&new);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert!(
+        diff.added_criteria.is_empty()
+            && diff.removed_criteria.is_empty()
+            && diff.changed_criteria.is_empty()
+    );
+    let changed = &diff.changed["foo"];
+    assert_eq!(1, changed.len());
+    assert_eq!(vec!["safe-to-run"], changed[0].0.criteria);
+    assert_eq!(vec!["safe-to-deploy"], changed[0].1.criteria);
+    assert_eq!(1, diff.changed.len());
+
+    let diff = new.diff(&vet::AuditsFile {
+        audits: BTreeMap::new(),
+        criteria: BTreeMap::new(),
+    });
+    assert_eq!(2, diff.removed["foo"].len());
+    assert_eq!(new.criteria.len(), diff.removed_criteria.len());
+}
+
+#[test]
+fn git_revision_of_violation_in_notes() {
+    let mut crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        r.package.revision_type = "git".into();
+        r.package.revision = "0123abcd".into();
+    });
+    crevette.set_include_git_revs(true);
+    let entry = only_entry(&crevette, "foo");
+    assert_eq!(Some("=1.0.0"), entry.violation.as_deref());
+    assert!(entry
+        .notes
+        .unwrap()
+        .contains("Reviewed at git revision 0123abcd"));
+}
+
+#[test]
+fn git_only_crates() {
+    let a = alice();
+    let from_git = |revision: &str| {
+        review_proof(&a, "internal", "0.3.0", good_review(), |r| {
+            r.package.id.id.source = "https://github.com/example/internal".into();
+            r.package.revision_type = "git".into();
+            r.package.revision = revision.into();
+        })
+    };
+
+    let mut crevette = crevette_for(&a, vec![from_git("0123abcd")]);
+    assert!(crevette.convert_to_document().unwrap().audits.is_empty());
+
+    crevette.set_git_sources(vec!["https://github.com/example/internal".into()]);
+    let entry = only_entry(&crevette, "internal");
+    assert_eq!(Some("0.3.0@git:0123abcd"), entry.version.as_deref());
+
+    let mut crevette = crevette_for(&a, vec![from_git("")]);
+    crevette.set_git_sources(vec!["https://github.com/example/internal".into()]);
+    assert!(crevette.convert_to_document().unwrap().audits.is_empty());
+}
+
+#[test]
+#[cfg(feature = "cargo-lock")]
+fn cargo_lock_audits() {
+    let lock = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["foo"]
+
+[[package]]
+name = "foo"
+version = "1.2.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "0000000000000000000000000000000000000000000000000000000000000000"
+
+[[package]]
+name = "bar"
+version = "0.5.0"
+source = "git+https://github.com/example/bar#0123abcd"
+"#;
+    let audits = audits_from_cargo_lock(lock, "alice", &["safe-to-run"]).unwrap();
+    assert_eq!(vec!["foo"], audits.keys().collect::<Vec<_>>());
+    let foo = &audits["foo"][0];
+    assert_eq!(Some("1.2.3"), foo.version.as_deref());
+    assert_eq!(vec!["safe-to-run"], foo.criteria);
+}
+
+#[test]
+fn provenance_can_be_hidden() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let entry = only_entry(&crevette, "foo");
+    assert_eq!(2, entry.aggregated_from.len());
+    assert!(entry.aggregated_from[1].starts_with("crev:review/"));
+
+    crevette.set_include_provenance(false);
+    let entry = only_entry(&crevette, "foo");
+    assert!(entry.aggregated_from.is_empty());
+}
+
+#[test]
+fn cve_and_ghsa_ids_are_linked() {
+    let mut crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        r.advisories.push(crev_data::review::Advisory {
+            ids: vec!["CVE-2022-12345".into(), "GHSA-abcd-efgh-ijkl".into()],
+            severity: Level::High,
+            ..Default::default()
+        });
+    });
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert!(notes.contains("https://nvd.nist.gov/vuln/detail/CVE-2022-12345"));
+    assert!(notes.contains("https://github.com/advisories/GHSA-abcd-efgh-ijkl"));
+    assert!(notes.contains("severity: high"));
+
+    crevette.set_advisory_severity_names(true);
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert!(notes.contains("severity: critical"));
+}
+
+#[test]
+fn violations_only() {
+    let a = alice();
+    let proofs = vec![
+        review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+        review_proof(&a, "bar", "1.0.0", Review::new_negative(), |_| {}),
+    ];
+    let crevette = crevette_for(&a, proofs);
+    let doc = crevette.convert_violations_to_document().unwrap();
+    assert!(!doc.audits.contains_key("foo"));
+    assert!(doc.audits["bar"].iter().all(|e| e.violation.is_some()));
+}
+
+#[cfg(feature = "debcargo")]
+#[test]
+fn download_retries_with_backoff() {
+    use std::cell::{Cell, RefCell};
+    use std::time::Duration;
+
+    let failure = |retry_after| AttemptError {
+        error: Error::IO(io::Error::new(io::ErrorKind::Other, "503")),
+        retryable: true,
+        retry_after,
+    };
+    let calls = Cell::new(0);
+    let sleeps = RefCell::new(vec![]);
+    let res = retry_with_backoff(
+        3,
+        Duration::from_secs(1),
+        |d| sleeps.borrow_mut().push(d),
+        || {
+            calls.set(calls.get() + 1);
+            match calls.get() {
+                1 => Err(failure(None)),
+                2 => Err(failure(Some(Duration::from_secs(5)))),
+                _ => Ok("done"),
+            }
+        },
+    );
+    assert_eq!("done", res.unwrap());
+    assert_eq!(3, calls.get());
+    assert_eq!(
+        vec![Duration::from_secs(1), Duration::from_secs(5)],
+        *sleeps.borrow()
+    );
+
+    calls.set(0);
+    let res: Result<(), _> = retry_with_backoff(
+        3,
+        Duration::ZERO,
+        |_| {},
+        || {
+            calls.set(calls.get() + 1);
+            Err(failure(None))
+        },
+    );
+    assert!(res.is_err());
+    assert_eq!(3, calls.get());
+}
+
+#[test]
+fn header_can_be_changed() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    assert!(crevette
+        .convert_to_toml()
+        .unwrap()
+        .starts_with("# Automatically generated"));
+
+    crevette.set_header_override(Some("Exported from crev".into()));
+    assert!(crevette
+        .convert_to_toml()
+        .unwrap()
+        .starts_with("# Exported from crev\n\n"));
+
+    crevette.set_include_header(false);
+    assert!(!crevette.convert_to_toml().unwrap().starts_with('#'));
+}
+
+#[test]
+fn raw_audits_url_only_for_known_hosts() {
+    assert_eq!(
+        raw_audits_url("https://github.com/alice/crev-proofs.git", "audits.toml"),
+        Some((
+            "https://raw.githubusercontent.com/alice/crev-proofs/HEAD/audits.toml".into(),
+            "alice".into()
+        ))
+    );
+    assert_eq!(
+        raw_audits_url("https://gitlab.com/alice/crev-proofs", "deploy-audits.toml"),
+        Some((
+            "https://gitlab.com/alice/crev-proofs/-/raw/HEAD/deploy-audits.toml".into(),
+            "alice".into()
+        ))
+    );
+    assert_eq!(
+        raw_audits_url("https://git.example.com/alice/crev-proofs", "audits.toml"),
+        None
+    );
+}
+
+#[test]
+fn url_status_of_proofs_repo() {
+    let crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let dir = TempDir::new("url-status");
+    let repo = git2::Repository::init(dir.path()).unwrap();
+
+    let no_remote = crevette.convert_into_repo_at(dir.path());
+    repo.remote("origin", "https://github.com/alice/crev-proofs.git")
+        .unwrap();
+    let github = crevette.convert_into_repo_at(dir.path());
+    repo.remote_set_url("origin", "https://git.example.com/alice/crev-proofs")
+        .unwrap();
+    let unsupported = crevette.convert_into_repo_at(dir.path());
+
+    let no_remote = no_remote.unwrap();
+    assert_eq!(UrlStatus::NoRemote, no_remote.url_status);
+    assert_eq!(None, no_remote.repo_git_url);
+    let github = github.unwrap();
+    assert_eq!(UrlStatus::Supported, github.url_status);
+    assert_eq!(
+        Some("https://raw.githubusercontent.com/alice/crev-proofs/HEAD/audits.toml"),
+        github.repo_https_url.as_deref()
+    );
+    assert_eq!(UrlStatus::Unsupported, unsupported.unwrap().url_status);
+}
+
+#[test]
+fn atomic_write_replaces_file() {
+    let dir = TempDir::new("atomic");
+    let path = dir.path().join("audits.toml");
+    std::fs::write(&path, "old").unwrap();
+
+    write_atomically(&path, b"new").unwrap();
+    assert_eq!("new", std::fs::read_to_string(&path).unwrap());
+    assert!(!dir.path().join("audits.toml.tmp").exists());
+    std::fs::remove_dir_all(dir.path()).unwrap();
+
+    // The temporary file can't be created, and the target is left alone
+    assert!(write_atomically(&path, b"new").is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn checksum_in_sha256sum_format() {
+    assert_eq!(
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  audits.toml\n",
+        sha256sum_line(b"", "audits.toml")
+    );
+    assert_eq!(
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  deploy-audits.toml\n",
+        sha256sum_line(b"hello", "deploy-audits.toml")
+    );
+}
+
+#[test]
+fn https_repo_url_of_various_remotes() {
+    for url in [
+        "https://github.com/alice/crev-proofs",
+        "https://github.com/alice/crev-proofs/",
+        "https://github.com/alice/crev-proofs.git",
+        "https://github.com/alice/crev-proofs.git/",
+        "https://github.com/alice/crev-proofs/.git",
+        "git@github.com:alice/crev-proofs.git",
+        "git@github.com:alice/crev-proofs.git/",
+        "ssh://git@github.com/alice/crev-proofs.git",
+        "ssh://git@github.com:22/alice/crev-proofs",
+    ] {
+        assert_eq!(
+            "https://github.com/alice/crev-proofs",
+            https_repo_url(url),
+            "{url}"
+        );
+    }
+    assert_eq!(
+        "https://alice.github.io/proofs.git.example",
+        https_repo_url("https://alice.github.io/proofs.git.example")
+    );
+    assert_eq!(
+        raw_audits_url("git@gitlab.com:alice/crev-proofs.git/", "audits.toml")
+            .unwrap()
+            .0,
+        "https://gitlab.com/alice/crev-proofs/-/raw/HEAD/audits.toml"
+    );
+}
+
+#[test]
+fn safe_to_run_by_understanding() {
+    let review = Review {
+        thoroughness: Level::None,
+        understanding: Level::Medium,
+        rating: Rating::Positive,
+    };
+    let mut crevette = alice_crevette("foo", "1.0.0", review, |_| {});
+    let entry = only_entry(&crevette, "foo");
+    assert!(!entry.criteria.contains(&"safe-to-run"));
+    assert!(!entry.criteria.contains(&"safe-to-deploy"));
+
+    crevette.set_safe_to_run_by_understanding(true);
+    let entry = only_entry(&crevette, "foo");
+    assert!(entry.criteria.contains(&"safe-to-run"));
+    assert!(!entry.criteria.contains(&"safe-to-deploy"));
+}
+
+#[test]
+fn reverse_dependencies_in_notes() {
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    assert_eq!(None, only_entry(&crevette, "foo").notes);
+
+    crevette.set_reverse_dependencies(Some([("foo".to_string(), 1234)].into_iter().collect()));
+    assert_eq!(
+        Some("widely used (1234 reverse deps)"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+    assert_eq!(None, only_entry(&crevette, "bar").notes);
+}
+
+#[test]
+fn source_override_collects_mirror_reviews() {
+    let mirror = "https://crates.mirror.example.com";
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |p| {
+        p.package.id.id.source = mirror.into();
+    });
+    assert!(crevette.convert_to_document().unwrap().audits.is_empty());
+
+    crevette.set_source_override(Some(mirror.into()));
+    only_entry(&crevette, "foo");
+}
+
+#[test]
+fn covered_crates_match_document() {
+    let a = alice();
+    let shallow = Review {
+        thoroughness: Level::None,
+        understanding: Level::None,
+        rating: Rating::Positive,
+    };
+    let weak = Review {
+        thoroughness: Level::Low,
+        understanding: Level::Low,
+        rating: Rating::Positive,
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "bar", "1.0.0", shallow, |_| {}),
+            review_proof(&a, "baz", "1.0.0", weak, |_| {}),
+        ],
+    );
+    let covered = crevette.covered_crates();
+    assert_eq!(
+        covered,
+        ["baz".to_string(), "foo".into()].into_iter().collect()
+    );
+    assert_eq!(
+        covered,
+        crevette
+            .convert_to_document()
+            .unwrap()
+            .audits
+            .into_keys()
+            .collect()
+    );
+
+    // Audits of baz have no built-in criteria, so they're left out
+    crevette.set_allowed_criteria(Some(
+        vet::BUILT_IN_CRITERIA
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+    ));
+    let covered = crevette.covered_crates();
+    assert_eq!(covered, ["foo".to_string()].into_iter().collect());
+    assert_eq!(
+        covered,
+        crevette
+            .convert_to_document()
+            .unwrap()
+            .audits
+            .into_keys()
+            .collect()
+    );
+    assert_eq!(
+        covered,
+        crevette.best_review_per_crate().into_keys().collect()
+    );
+}
+
+#[test]
+fn long_notes_are_truncated() {
+    let comment = format!(
+        "Looks fine.\n{}",
+        "All of it was checked. ".repeat(100).trim_end()
+    );
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |p| {
+        p.comment = comment.clone()
+    });
+    assert!(!only_entry(&crevette, "foo")
+        .notes
+        .unwrap()
+        .contains("truncated"));
+
+    crevette.set_max_note_len(Some(50));
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert!(
+        notes.starts_with("Looks fine.\n… (truncated, see crev review "),
+        "{notes}"
+    );
+
+    crevette.set_max_note_len(Some(5));
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert!(notes.starts_with("Looks\n… (truncated"), "{notes}");
+}
+
+#[test]
+fn who_on_enterprise_hosts() {
+    let a = alice();
+    let who = |url: &str| {
+        author_from_id(
+            a.as_public_id(),
+            Some(&Url::new_git(url)),
+            WhoFormat::NameUrl,
+        )
+    };
+    assert_eq!(
+        "\"user\" (https://github.example.com/user)",
+        who("https://github.example.com/user/crev-proofs")
+    );
+    assert_eq!(
+        "\"user\" (https://git.example.org/user)",
+        who("https://git.example.org/user/crev-proofs")
+    );
+    assert_eq!(
+        "\"example.org\" (https://example.org/a/b/c)",
+        who("https://example.org/a/b/c")
+    );
+}
+
+#[test]
+fn document_since_only_has_newer_reviews() {
+    let a = alice();
+    let date = |s: &str| proof::Date::parse_from_rfc3339(s).unwrap();
+    let crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |p| {
+                p.common.date = date("2020-01-01T00:00:00Z")
+            }),
+            review_proof(&a, "bar", "1.0.0", good_review(), |p| {
+                p.common.date = date("2024-01-01T00:00:00Z")
+            }),
+        ],
+    );
+    assert_eq!(2, crevette.convert_to_document().unwrap().audits.len());
+
+    let doc = crevette
+        .convert_to_document_since(&date("2022-01-01T00:00:00Z"))
+        .unwrap();
+    assert_eq!(vec!["bar"], doc.audits.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn tied_reviews_sort_by_reviewer() {
+    let date = proof::Date::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+    let packages: Vec<Package> = [
+        "https://github.com/alice/crev-proofs",
+        "https://github.com/bob/crev-proofs",
+    ]
+    .iter()
+    .map(|url| {
+        let mut package = UnlockedId::generate_for_git_url(url)
+            .as_public_id()
+            .create_package_review_proof(
+                package_info("foo", "1.0.0"),
+                good_review(),
+                vec![],
+                String::new(),
+            )
+            .unwrap();
+        package.common.date = date;
+        package
+    })
+    .collect();
+    let sorted_ids = |order: [usize; 2]| {
+        let mut reviews: Vec<_> = order
+            .iter()
+            .map(|&i| ScoredReview {
+                review: &packages[i],
+                trust: TrustLevel::High,
+                review_quality_score: 14,
+            })
+            .collect();
+        sort_by_preference(&mut reviews);
+        reviews
+            .iter()
+            .map(|s| s.review.common.from.id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert!(sorted_ids([0, 1]) == sorted_ids([1, 0]));
+}
+
+#[test]
+fn best_review_is_the_first_exported() {
+    let a = alice();
+    let shallow = Review {
+        thoroughness: Level::None,
+        understanding: Level::None,
+        rating: Rating::Positive,
+    };
+    let crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "foo", "1.1.0", good_review(), |_| {}),
+            review_proof(&a, "foo", "2.0.0", shallow, |_| {}),
+        ],
+    );
+    let best = crevette.best_review_per_crate();
+    assert_eq!("1.1.0", best["foo"].package.id.version.to_string());
+
+    let doc = crevette.convert_to_document().unwrap();
+    assert_eq!(Some("1.1.0"), doc.audits["foo"][0].version.as_deref());
+}
+
+#[cfg(feature = "aggregate")]
+#[test]
+fn aggregated_audits_are_tagged() {
+    let url = "https://raw.githubusercontent.com/bob/crev-proofs/HEAD/audits.toml";
+    let toml = r#"
+[[audits.foo]]
+who = "bob"
+criteria = ["safe-to-deploy", "bobs-own"]
+version = "1.0.0"
+
+[[audits.bar]]
+who = "bob"
+criteria = "bobs-own"
+version = "1.0.0"
+"#;
+    let known: Vec<_> = vet::BUILT_IN_CRITERIA
+        .into_iter()
+        .chain(standard_criteria(DEFAULT_TRUST_CRITERIA_NAMES).into_keys())
+        .collect();
+    let audits = aggregate::parse_audits(url, toml, &known).unwrap();
+    assert_eq!(1, audits.len());
+    let (name, entry) = &audits[0];
+    assert_eq!("foo", name);
+    assert_eq!(vec!["safe-to-deploy"], entry.criteria);
+    assert_eq!(vec![url.to_string()], entry.aggregated_from);
+}
+
+#[cfg(feature = "debcargo")]
+#[test]
+fn debian_who_ignores_case_and_whitespace() {
+    let who = debian_who(
+        Some("Jane Doe".into()),
+        Some("jane@example.com".into()),
+        &[
+            "jane  doe <Jane@Example.com>".into(),
+            "John Roe <john@example.com>".into(),
+        ],
+    );
+    assert_eq!(
+        who,
+        [
+            "\"Jane Doe\" <jane@example.com>",
+            "\"John Roe\" <john@example.com>"
+        ]
+    );
+}
+
+#[cfg(feature = "debcargo")]
+#[test]
+fn debcargo_audits_from_sources_fixture() {
+    use std::io::Write;
+
+    let sources = "\
+Package: rust-foo
+Binary: librust-foo-dev
+Version: 1.2.3-1
+Maintainer: Debian Rust Maintainers <pkg-rust-maintainers@alioth-lists.debian.net>
+Uploaders: Jane Doe <jane@example.com>
+Directory: pool/main/r/rust-foo
+
+Package: rust-bar
+Binary: librust-bar-dev
+Version: 0.4.0-2
+Maintainer: Debian Rust Maintainers <pkg-rust-maintainers@alioth-lists.debian.net>
+Directory: pool/main/r/rust-bar
+";
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(sources.as_bytes()).unwrap();
+    let gz = gz.finish().unwrap();
+
+    let dir = TempDir::new("sources");
+    let audits = Crevette::debcargo_audits_from_sources(dir.path(), "stable", &gz[..], None);
+    let audits = audits.unwrap().audits;
+
+    assert_eq!(
+        vec!["bar", "foo"],
+        audits.keys().map(String::as_str).collect::<Vec<_>>()
+    );
+    assert_eq!(Some("1.2.3"), audits["foo"][0].version.as_deref());
+    assert_eq!(Some("0.4.0"), audits["bar"][0].version.as_deref());
+    assert_eq!(
+        audits["foo"][0].who.as_vec(),
+        [
+            "\"Debian Rust Maintainers\" <pkg-rust-maintainers@alioth-lists.debian.net>",
+            "\"Jane Doe\" <jane@example.com>",
+        ]
+    );
+    assert_eq!(
+        audits["bar"][0].who.as_vec(),
+        ["\"Debian Rust Maintainers\" <pkg-rust-maintainers@alioth-lists.debian.net>"]
+    );
+}
+
+#[cfg(feature = "debcargo")]
+#[test]
+fn debian_who_sorts_uploaders() {
+    let uploaders = [
+        "Zed Doe <zed@example.com>".to_string(),
+        "amy Roe <amy@example.com>".into(),
+        "Bob Poe <bob@example.com>".into(),
+    ];
+    let who = debian_who(
+        Some("Jane Doe".into()),
+        Some("jane@example.com".into()),
+        &uploaders,
+    );
+    assert_eq!(
+        who,
+        [
+            "\"Jane Doe\" <jane@example.com>",
+            "\"amy Roe\" <amy@example.com>",
+            "\"Bob Poe\" <bob@example.com>",
+            "\"Zed Doe\" <zed@example.com>",
+        ]
+    );
+
+    let mut reversed = uploaders.clone();
+    reversed.reverse();
+    assert_eq!(
+        who,
+        debian_who(
+            Some("Jane Doe".into()),
+            Some("jane@example.com".into()),
+            &reversed
+        )
+    );
+}
+
+#[test]
+fn neutral_review_by_highly_trusted_reviewer() {
+    let review = Review {
+        thoroughness: Level::Medium,
+        understanding: Level::Low,
+        rating: Rating::Neutral,
+    };
+    let mut crevette = alice_crevette("foo", "1.0.0", review, |_| {});
+    assert!(!only_entry(&crevette, "foo")
+        .criteria
+        .contains(&"safe-to-run"));
+
+    crevette.set_relax_neutral_for_high_trust(true);
+    let entry = only_entry(&crevette, "foo");
+    assert!(entry.criteria.contains(&"safe-to-run"));
+    assert!(entry.criteria.contains(&"trust-high"));
+}
+
+#[test]
+fn min_understanding_for_deploy() {
+    let review = Review {
+        thoroughness: Level::High,
+        understanding: Level::Medium,
+        rating: Rating::Strong,
+    };
+    let mut crevette = alice_crevette("foo", "1.0.0", review, |_| {});
+    assert!(only_entry(&crevette, "foo")
+        .criteria
+        .contains(&"safe-to-deploy"));
+
+    crevette.set_min_understanding_for_deploy(Level::High);
+    let entry = only_entry(&crevette, "foo");
+    assert!(!entry.criteria.contains(&"safe-to-deploy"));
+    assert!(entry.criteria.contains(&"safe-to-run"));
+}
+
+#[test]
+fn criteria_toml_has_all_criteria() {
+    let a = alice();
+    let crevette = crevette_for(&a, vec![]);
+    let criteria: BTreeMap<String, BTreeMap<String, serde_json::Value>> =
+        toml_edit::de::from_str(&crevette.criteria_toml().unwrap()).unwrap();
+    assert_eq!(
+        criteria.keys().collect::<Vec<_>>(),
+        standard_criteria(DEFAULT_TRUST_CRITERIA_NAMES)
+            .keys()
+            .collect::<Vec<_>>()
+    );
+    assert!(criteria["trust-high"].contains_key("description"));
+}
+
+#[test]
+fn max_entries_per_crate_keeps_violations() {
+    let a = alice();
+    // Older versions have better reviews, so none of them is pareto-worse
+    let review = |thoroughness, understanding| Review {
+        thoroughness,
+        understanding,
+        rating: Rating::Positive,
+    };
+    let negative = Review {
+        rating: Rating::Negative,
+        ..good_review()
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.4.0", review(Level::Low, Level::Low), |_| {}),
+            review_proof(
+                &a,
+                "foo",
+                "1.3.0",
+                review(Level::Medium, Level::Low),
+                |_| {},
+            ),
+            review_proof(
+                &a,
+                "foo",
+                "1.2.0",
+                review(Level::Medium, Level::Medium),
+                |_| {},
+            ),
+            review_proof(
+                &a,
+                "foo",
+                "1.1.0",
+                review(Level::High, Level::Medium),
+                |_| {},
+            ),
+            review_proof(&a, "foo", "1.0.0", review(Level::High, Level::High), |_| {}),
+            review_proof(&a, "foo", "0.9.0", negative, |_| {}),
+        ],
+    );
+    assert_eq!(
+        6,
+        crevette.convert_to_document().unwrap().audits["foo"].len()
+    );
+
+    crevette.set_max_entries_per_crate(Some(2));
+    let doc = crevette.convert_to_document().unwrap();
+    let versions: Vec<_> = doc.audits["foo"]
+        .iter()
+        .map(|e| e.version.as_deref().or(e.violation.as_deref()))
+        .collect();
+    assert_eq!(versions, [Some("1.4.0"), Some("1.3.0"), Some("=0.9.0")]);
+}
+
+#[test]
+fn empty_audits_are_not_committed() {
+    let a = alice();
+    let crevette = crevette_for(&a, vec![]);
+    let dir = TempDir::new("no-such-repo");
+    let res = crevette.convert_into_repo_at(&dir.path().join("missing"));
+    assert!(matches!(res, Err(Error::NoReviewsExported)));
+}
+
+#[test]
+fn language_of_reviewer_comments() {
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |r| {
+                r.comment = "Sieht gut aus".into()
+            }),
+            review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    crevette.set_reviewer_languages([(a.id.id.clone(), "de".to_string())].into_iter().collect());
+    assert_eq!(
+        Some("[lang: de] Sieht gut aus"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+    assert_eq!(None, only_entry(&crevette, "bar").notes);
+
+    crevette.set_note_format(NoteFormat::Markdown);
+    assert!(only_entry(&crevette, "foo")
+        .notes
+        .unwrap()
+        .starts_with("[lang: de]\n\n## Reviewer comment"));
+}
+
+#[test]
+fn selected_reviews_match_audits() {
+    let a = alice();
+    let weak = Review {
+        thoroughness: Level::Low,
+        understanding: Level::Low,
+        rating: Rating::Positive,
+    };
+    let crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            // pareto-worse than the review of 1.0.0
+            review_proof(&a, "foo", "0.9.0", weak, |_| {}),
+            review_proof(&a, "foo", "0.8.0", Review::new_negative(), |_| {}),
+        ],
+    );
+    let selected = crevette.selected_reviews();
+    let versions: Vec<_> = selected["foo"]
+        .iter()
+        .map(|r| r.package.id.version.to_string())
+        .collect();
+    assert_eq!(versions, ["1.0.0", "0.8.0"]);
+
+    let doc = crevette.convert_to_document().unwrap();
+    assert_eq!(doc.audits["foo"].len(), selected["foo"].len());
+}
+
+#[test]
+fn only_allowed_criteria() {
+    let a = alice();
+    let weak = Review {
+        thoroughness: Level::Low,
+        understanding: Level::Low,
+        rating: Rating::Positive,
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "bar", "1.0.0", weak, |_| {}),
+        ],
+    );
+    assert!(only_entry(&crevette, "bar")
+        .criteria
+        .iter()
+        .all(|c| !vet::BUILT_IN_CRITERIA.contains(c)));
+
+    crevette.set_allowed_criteria(Some(
+        vet::BUILT_IN_CRITERIA
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+    ));
+    assert_eq!(
+        vec!["safe-to-deploy", "safe-to-run"],
+        only_entry(&crevette, "foo").criteria
+    );
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(!doc.audits.contains_key("bar"));
+    assert!(doc.criteria.is_empty());
+
+    crevette.set_allowed_criteria(Some(
+        ["safe-to-run".to_string(), "trust-high".into()]
+            .into_iter()
+            .collect(),
+    ));
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(doc.criteria["trust-high"].implies.is_empty());
+}
+
+#[test]
+fn proofs_loaded_from_dir() {
+    let a = alice();
+    let dir = TempDir::new("proofs");
+    std::fs::create_dir_all(dir.path().join("reviews")).unwrap();
+    let proof = review_proof(&a, "foo", "1.0.0", good_review(), |_| {});
+    std::fs::write(dir.path().join("reviews/foo.proof.crev"), proof.to_string()).unwrap();
+
+    let crevette = Crevette::from_proof_db_path(
+        dir.path(),
+        a.as_ref(),
+        &TrustDistanceParams::default(),
+        TrustLevel::Low,
+    )
+    .unwrap();
+    std::fs::remove_dir_all(dir.path()).unwrap();
+    assert!(only_entry(&crevette, "foo")
+        .criteria
+        .contains(&"safe-to-deploy"));
+
+    assert!(Crevette::from_proof_db_path(
+        dir.path(),
+        a.as_ref(),
+        &TrustDistanceParams::default(),
+        TrustLevel::Low
+    )
+    .is_err());
+}
+
+#[test]
+fn custom_trust_criteria_names() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    crevette.set_trust_criteria_names([
+        "reviewed-externally",
+        "reviewed-by-partners",
+        "reviewed-internally",
+    ]);
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(doc.audits["foo"][0]
+        .criteria
+        .contains(&"reviewed-internally"));
+    assert!(!doc.audits["foo"][0].criteria.contains(&"trust-high"));
+    assert_eq!(
+        doc.criteria["reviewed-internally"].implies,
+        ["reviewed-by-partners"]
+    );
+    assert!(doc.criteria.contains_key("reviewed-externally"));
+    assert!(!doc.criteria.contains_key("trust-low"));
+}
+
+#[test]
+fn scores_in_notes() {
+    let review = Review {
+        thoroughness: Level::Medium,
+        understanding: Level::High,
+        rating: Rating::Positive,
+    };
+    let mut crevette = alice_crevette("foo", "1.0.0", review, |_| {});
+    crevette.set_annotate_scores(true);
+    assert_eq!(
+        Some("crev: trust=High, thoroughness=Medium, understanding=High (score 10)"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+}
+
+#[cfg(feature = "opensuse")]
+#[test]
+fn opensuse_primary_xml() {
+    let primary = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata xmlns="http://linux.duke.edu/metadata/common" packages="3">
+<package type="rpm">
+  <name>rust-foo</name>
+  <arch>src</arch>
+  <version epoch="0" ver="1.2.0~rc1" rel="1.2"/>
+</package>
+<package type="rpm">
+  <name>rust-bar0.1</name>
+  <arch>src</arch>
+  <version epoch="0" ver="0.1" rel="1.1"/>
+</package>
+<package type="rpm">
+  <name>ripgrep</name>
+  <arch>src</arch>
+  <version epoch="0" ver="14.1.0" rel="1.1"/>
+</package>
+</metadata>"#;
+    let crates = opensuse::list_all(primary);
+    assert_eq!(1, crates.len());
+    assert_eq!("foo", crates[0].name);
+    assert_eq!("1.2.0-rc1", crates[0].version);
+    assert_eq!(
+        "https://build.opensuse.org/package/show/openSUSE:Factory/rust-foo",
+        opensuse::obs_package_url(&crates[0].package)
+    );
+}
+
+#[cfg(feature = "sbom")]
+#[test]
+fn sbom_components() {
+    let cyclonedx = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.5",
+  "components": [
+    {"type": "application", "name": "app", "purl": "pkg:cargo/app@0.1.0?repository_url=https://registry.example.com",
+     "components": [
+       {"type": "library", "name": "serde", "version": "1.0.188", "purl": "pkg:cargo/serde@1.0.188", "supplier": {"name": "Serde Devs"}}
+     ]},
+    {"type": "library", "name": "openssl", "purl": "pkg:generic/openssl@3.0.0"},
+    {"type": "library", "name": "foo", "purl": "pkg:cargo/foo@1.0.0%2Bbuild.1"}
+  ]
+}"#;
+    let mut crates = sbom::list_all(cyclonedx, SbomFormat::CycloneDx).unwrap();
+    crates.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(2, crates.len());
+    assert_eq!(
+        ("foo", "1.0.0+build.1", None),
+        (
+            crates[0].name.as_str(),
+            crates[0].version.as_str(),
+            crates[0].supplier.as_deref()
+        )
+    );
+    assert_eq!(
+        ("serde", "1.0.188", Some("Serde Devs")),
+        (
+            crates[1].name.as_str(),
+            crates[1].version.as_str(),
+            crates[1].supplier.as_deref()
+        )
+    );
+
+    let dir = TempDir::new("sbom");
+    let path = dir.path().join("sbom.json");
+    std::fs::write(&path, cyclonedx).unwrap();
+    let toml = Crevette::from_sbom(&path, SbomFormat::CycloneDx).unwrap();
+    let audits: BTreeMap<String, BTreeMap<String, Vec<BTreeMap<String, serde_json::Value>>>> =
+        toml_edit::de::from_str(&toml).unwrap();
+    let serde = &audits["audits"]["serde"][0];
+    assert_eq!(serde_json::json!(["Serde Devs"]), serde["who"]);
+    assert_eq!(serde_json::json!("1.0.188"), serde["version"]);
+    assert_eq!(serde_json::json!(["safe-to-run"]), serde["criteria"]);
+    let sbom_name = path.file_name().unwrap().to_string_lossy();
+    assert_eq!(
+        serde_json::json!([format!("SBOM {sbom_name}")]),
+        audits["audits"]["foo"][0]["who"]
+    );
+
+    let spdx = r#"{
+  "spdxVersion": "SPDX-2.3",
+  "packages": [
+    {"name": "serde", "versionInfo": "1.0.188", "supplier": "Organization: Serde Devs (serde@example.com)",
+     "externalRefs": [{"referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": "pkg:cargo/serde@1.0.188"}]},
+    {"name": "bar", "supplier": "NOASSERTION",
+     "externalRefs": [{"referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": "pkg:cargo/bar@0.2.0"}]},
+    {"name": "libc-dev", "externalRefs": []}
+  ]
+}"#;
+    let crates = sbom::list_all(spdx, SbomFormat::Spdx).unwrap();
+    assert_eq!(2, crates.len());
+    assert_eq!(Some("Serde Devs"), crates[0].supplier.as_deref());
+    assert_eq!(
+        ("bar", "0.2.0", None),
+        (
+            crates[1].name.as_str(),
+            crates[1].version.as_str(),
+            crates[1].supplier.as_deref()
+        )
+    );
+
+    assert_eq!(None, sbom::crate_from_purl("pkg:cargo/serde"));
+    assert_eq!(
+        Some(("serde".into(), "1.0.188".into())),
+        sbom::crate_from_purl("pkg:cargo/serde@1.0.188#src")
+    );
+}
+
+#[test]
+fn trusted_publishers() {
+    let a = alice();
+    let b = UnlockedId::generate_for_git_url("https://github.com/bob/crev-proofs");
+    let crevette = crevette_for(&a, vec![]);
+    let publishers = [
+        (
+            a.id.id.clone(),
+            CratesIoPublisher {
+                user_id: 1,
+                crates: vec!["foo".into()],
+            },
+        ),
+        (
+            b.id.id.clone(),
+            CratesIoPublisher {
+                user_id: 2,
+                crates: vec!["bar".into()],
+            },
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let doc = crevette.convert_to_trusted_document(&publishers, "2024-01-01", "2025-01-01");
+    assert_eq!(vec!["foo"], doc.trusted.keys().collect::<Vec<_>>());
+    assert_eq!(1, doc.trusted["foo"][0].user_id);
+    assert_eq!("2025-01-01", doc.trusted["foo"][0].end);
+}
+
+#[test]
+fn emit_all_reviews_keeps_pareto_worse() {
+    let a = alice();
+    let worse = Review {
+        thoroughness: Level::Medium,
+        understanding: Level::Medium,
+        rating: Rating::Positive,
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.1.0", good_review(), |_| {}),
+            review_proof(&a, "foo", "1.0.0", worse, |_| {}),
+        ],
+    );
+    assert_eq!(
+        1,
+        crevette.convert_to_document().unwrap().audits["foo"].len()
+    );
+
+    crevette.set_emit_all_reviews(true);
+    assert_eq!(
+        2,
+        crevette.convert_to_document().unwrap().audits["foo"].len()
+    );
+}
+
+#[test]
+fn reviews_by_distrusted_reviewers() {
+    let a = alice();
+    let b = UnlockedId::generate_for_git_url("https://github.com/bob/crev-proofs");
+    let distrust = a
+        .create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Distrust, vec![])
+        .unwrap();
+    let crevette = crevette_for(
+        &a,
+        vec![
+            distrust,
+            review_proof(&b, "foo", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    assert!(crevette.convert_to_document().unwrap().audits.is_empty());
+
+    let distrusted = crevette.distrusted_reviews();
+    assert_eq!(1, distrusted.len());
+    assert_eq!("foo", distrusted[0].package.id.id.name);
+}
+
+#[test]
+fn stricter_trust_for_some_crates() {
+    let a = alice();
+    let b = UnlockedId::generate_for_git_url("https://github.com/bob/crev-proofs");
+    let trust = a
+        .create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Low, vec![])
+        .unwrap();
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            trust,
+            review_proof(&b, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&b, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    assert_eq!(2, crevette.convert_to_document().unwrap().audits.len());
+
+    crevette.set_crate_trust_overrides(
+        [("foo".to_string(), TrustLevel::Medium)]
+            .into_iter()
+            .collect(),
+    );
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(!doc.audits.contains_key("foo"));
+    assert!(doc.audits.contains_key("bar"));
+}
+
+#[test]
+fn custom_violation_criteria() {
+    let mut crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        let mut issue = crev_data::review::Issue::new("bad-docs".into());
+        issue.severity = Level::Low;
+        r.issues.push(issue);
+    });
+    assert_eq!(vec!["level-low"], only_entry(&crevette, "foo").criteria);
+
+    crevette.set_violation_criteria(Some(
+        [(Level::Low, vec!["minor-issue"])].into_iter().collect(),
+    ));
+    crevette.set_custom_criteria(
+        [(
+            "minor-issue",
+            vet::CriteriaEntry {
+                description: Some("Has minor problems".into()),
+                implies: vec![],
+                aggregated_from: vec![],
+            },
+        )]
+        .into_iter()
+        .collect(),
+    );
+    let doc = crevette.convert_to_document().unwrap();
+    assert_eq!(vec!["minor-issue"], doc.audits["foo"][0].criteria);
+    assert!(doc.criteria.contains_key("minor-issue"));
+}
+
+#[test]
+fn source_url_in_notes() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    assert_eq!(None, only_entry(&crevette, "foo").notes);
+
+    crevette.set_include_source_url(true);
+    assert_eq!(
+        Some("source: https://docs.rs/crate/foo/1.0.0/source/"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+}
+
+#[test]
+fn markdown_notes() {
+    let mut crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        r.comment = "Don't use it".into();
+        r.advisories.push(crev_data::review::Advisory {
+            ids: vec!["RUSTSEC-2021-0001".into()],
+            severity: Level::High,
+            comment: "Use after free".into(),
+            ..Default::default()
+        });
+    });
+    assert_eq!(
+        "Don't use it\nseverity: high\nid: RUSTSEC-2021-0001 <https://rustsec.org/advisories/RUSTSEC-2021-0001.html>\n\nUse after free",
+        only_entry(&crevette, "foo").notes.unwrap()
+    );
+
+    crevette.set_note_format(NoteFormat::Markdown);
+    assert_eq!(
+        "## Reviewer comment\n\nDon't use it\n\n## Advisories\n\n- **high** [RUSTSEC-2021-0001](https://rustsec.org/advisories/RUSTSEC-2021-0001.html): Use after free",
+        only_entry(&crevette, "foo").notes.unwrap()
+    );
+}
+
+#[test]
+fn yanked_versions() {
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    crevette.set_yanked_versions(
+        [("foo".to_string(), Version::parse("1.0.0").unwrap())]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(
+        Some("This version has been yanked"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+
+    crevette.set_skip_yanked(true);
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(!doc.audits.contains_key("foo"));
+    assert!(doc.audits.contains_key("bar"));
+}
+
+#[test]
+fn documents_by_criteria() {
+    let a = alice();
+    let crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "bar", "1.0.0", Review::new_negative(), |_| {}),
+        ],
+    );
+    let docs = crevette.convert_to_documents_by_criteria().unwrap();
+    assert_eq!(
+        vec!["safe-to-deploy", "violations"],
+        docs.keys().collect::<Vec<_>>()
+    );
+    assert!(docs["safe-to-deploy"].audits.contains_key("foo"));
+    assert!(docs["violations"].audits.contains_key("bar"));
+}
+
+#[test]
+fn criteria_block_can_be_left_out() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    assert!(!crevette.convert_to_document().unwrap().criteria.is_empty());
+
+    crevette.set_include_criteria_block(false);
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(doc.criteria.is_empty());
+    assert!(doc.audits.contains_key("foo"));
+    assert!(!crevette.convert_to_toml().unwrap().contains("[criteria."));
+}
+
+#[test]
+fn imported_reviews_have_digests() {
+    let crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    assert!(crevette.reviews_without_digest().is_empty());
+}
+
+#[test]
+fn custom_audit_portal_for_violations() {
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![review_proof(
+            &a,
+            "foo",
+            "1.0.0",
+            Review::new_negative(),
+            |_| {},
+        )],
+    );
+    assert_eq!(
+        Some("<https://lib.rs/crates/foo/audit>"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+
+    crevette.set_audit_portal_url_template("https://audits.example.com/crate/{name}".into());
+    assert_eq!(
+        Some("<https://audits.example.com/crate/foo>"),
+        only_entry(&crevette, "foo").notes.as_deref()
+    );
+
+    let proof = review_proof(&a, "bar", "1.0.0", good_review(), |r| {
+        r.flags.unmaintained = true
+    });
+    let mut crevette = crevette_for(&a, vec![proof]);
+    crevette.set_unmaintained_as_violation(true);
+    crevette.set_audit_portal_url_template("https://audits.example.com/crate/{name}".into());
+    let doc = crevette.convert_to_document().unwrap();
+    let unmaintained = doc.audits["bar"]
+        .iter()
+        .find(|e| e.violation.is_some())
+        .unwrap();
+    assert_eq!(
+        Some("Flagged as unmaintained <https://audits.example.com/crate/bar>"),
+        unmaintained.notes.as_deref()
+    );
+}
+
+#[test]
+fn audits_built_from_explicit_db_and_options() {
+    let a = alice();
+    let mut db = ProofDB::new();
+    db.import_from_iter(
+        [(
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            FetchSource::LocalUser,
+        )]
+        .into_iter(),
+    );
+    let trusts = db.calculate_trust_set(&a.id.id, &TrustDistanceParams::default());
+
+    let doc = build_audits(&db, &trusts, &ConvertOptions::default()).unwrap();
+    assert!(doc.audits.contains_key("foo"));
+
+    let opts = ConvertOptions {
+        allowed_criteria: Some(HashSet::new()),
+        ..ConvertOptions::default()
+    };
+    assert!(build_audits(&db, &trusts, &opts).unwrap().audits.is_empty());
+}
+
+#[test]
+fn reviewers_sharing_url_are_distinguished() {
+    let a = alice();
+    // Same repo, new key
+    let b = alice();
+    let trust =
+        a.id.create_trust_proof([b.as_public_id()], TrustLevel::High, vec![])
+            .unwrap()
+            .sign_by(&a)
+            .unwrap();
+    let crevette = crevette_for(
+        &a,
+        vec![
+            trust,
+            review_proof(&b, "foo", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+
+    let shared = crevette.shared_reviewer_urls();
+    assert_eq!(1, shared.len());
+    assert_eq!(2, shared["https://github.com/alice/crev-proofs"].len());
+
+    let b_id = b.id.id.to_string();
+    assert_eq!(
+        format!("\"alice\" (https://github.com/alice) [{}]", &b_id[..8]),
+        who_string(&only_entry(&crevette, "foo"))
+    );
+}
+
+#[test]
+fn neutral_reviews_can_be_left_out() {
+    let a = alice();
+    let neutral = Review {
+        rating: Rating::Neutral,
+        ..good_review()
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", neutral, |_| {}),
+            review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "baz", "1.0.0", Review::new_negative(), |_| {}),
+        ],
+    );
+    assert!(only_entry(&crevette, "foo").criteria.contains(&"neutral"));
+
+    crevette.set_include_neutral_reviews(false);
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(!doc.audits.contains_key("foo"));
+    assert!(doc.audits.contains_key("bar"));
+    assert!(doc.audits["baz"][0].violation.is_some());
+}
+
+#[test]
+fn toml_serialization_errors_are_not_io_errors() {
+    // A TOML document must be a table
+    assert!(matches!(to_toml(&1u32), Err(Error::TomlSerialization(_))));
+    assert!(to_toml(&BTreeMap::from([("a", 1u32)]))
+        .unwrap()
+        .contains("a = 1"));
+}
+
+#[test]
+fn self_reviews_can_be_left_out() {
+    let a = alice();
+    let b = UnlockedId::generate_for_git_url("https://github.com/bob/crev-proofs");
+    let trust =
+        a.id.create_trust_proof([b.as_public_id()], TrustLevel::High, vec![])
+            .unwrap()
+            .sign_by(&a)
+            .unwrap();
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            trust,
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&b, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    assert!(crevette
+        .convert_to_document()
+        .unwrap()
+        .audits
+        .contains_key("foo"));
+
+    crevette.set_exclude_self_reviews(true);
+    let doc = crevette.convert_to_document().unwrap();
+    assert!(!doc.audits.contains_key("foo"));
+    assert!(doc.audits.contains_key("bar"));
+}
+
+#[test]
+fn progress_reported_per_crate() {
+    use std::sync::{Arc, Mutex};
+
+    let a = alice();
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "foo", "1.1.0", good_review(), |_| {}),
+            review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+            review_proof(&a, "baz", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let r = reported.clone();
+    crevette.set_progress(move |p| r.lock().unwrap().push(p));
+    crevette.convert_to_document().unwrap();
+
+    let reported = reported.lock().unwrap();
+    assert_eq!(3, reported.len());
+    assert!(reported.iter().all(|p| p.total == 3));
+    assert_eq!(Progress { done: 3, total: 3 }, *reported.last().unwrap());
+}
+
+#[test]
+fn verbose_criteria_descriptions() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let description = |crevette: &Crevette, name: &str| {
+        crevette.convert_to_document().unwrap().criteria[name]
+            .description
+            .clone()
+            .unwrap()
+    };
+    assert!(!description(&crevette, "level-high").contains("Derived"));
+
+    crevette.set_verbose_criteria_descriptions(true);
+    assert!(description(&crevette, "level-high").ends_with(
+        "Derived from crev thoroughness+understanding >= 14 (none=0, low=1, medium=3, high=7)"
+    ));
+    assert!(description(&crevette, "level-none").contains("< 2"));
+    assert!(description(&crevette, "positive").contains("rating positive"));
+    assert!(description(&crevette, DEFAULT_TRUST_CRITERIA_NAMES[2]).contains("trust level high"));
+}
+
+#[cfg(feature = "guix")]
+#[test]
+fn guix_category_filter() {
+    assert!(is_selected_guix_category("games", None));
+    assert!(is_selected_guix_category(
+        "rust",
+        Some(&["rust", "crates-io"])
+    ));
+    assert!(!is_selected_guix_category(
+        "games",
+        Some(&["rust", "crates-io"])
+    ));
+    assert!(!is_selected_guix_category("rust", Some(&[])));
+}
+
+#[test]
+fn multiple_ids_use_highest_trust() {
+    let a = alice();
+    let c = UnlockedId::generate_for_git_url("https://github.com/carol/crev-proofs");
+    let b = UnlockedId::generate_for_git_url("https://github.com/bob/crev-proofs");
+    let low =
+        a.id.create_trust_proof([b.as_public_id()], TrustLevel::Low, vec![])
+            .unwrap()
+            .sign_by(&a)
+            .unwrap();
+    let high =
+        c.id.create_trust_proof([b.as_public_id()], TrustLevel::High, vec![])
+            .unwrap()
+            .sign_by(&c)
+            .unwrap();
+    let review = review_proof(&b, "foo", "1.0.0", good_review(), |_| {});
+
+    let mut db = ProofDB::new();
+    db.import_from_iter(
+        [low, high, review]
+            .into_iter()
+            .map(|p| (p, FetchSource::LocalUser)),
+    );
+    let crevette = Crevette::new_multi(
+        db,
+        &[a.id.id.clone(), c.id.id.clone()],
+        &TrustDistanceParams::default(),
+        TrustLevel::Low,
+    )
+    .unwrap();
+    assert_eq!(TrustLevel::High, crevette.scored_reviews()["foo"][0].trust);
+    assert!(only_entry(&crevette, "foo")
+        .criteria
+        .contains(&DEFAULT_TRUST_CRITERIA_NAMES[2]));
+
+    assert!(matches!(
+        Crevette::new_multi(
+            ProofDB::new(),
+            &[],
+            &TrustDistanceParams::default(),
+            TrustLevel::Low
+        ),
+        Err(Error::Crev(crev_lib::Error::NoIdsGiven))
+    ));
+}
+
+#[test]
+fn legacy_vet_schema_has_no_aggregated_from() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let toml = crevette.convert_to_toml().unwrap();
+    assert!(toml.contains("aggregated-from"));
+
+    crevette.set_vet_schema(vet::VetSchema::V1);
+    let toml = crevette.convert_to_toml().unwrap();
+    assert!(!toml.contains("aggregated-from"));
+    assert!(toml.contains("[[audits.foo]]"));
+    assert!(toml.contains("[criteria.level-high]"));
+}
+
+#[test]
+fn moved_reviewer_has_one_who() {
+    let mut a = alice();
+    let old = review_proof(&a, "foo", "1.0.0", good_review(), |_| {});
+    a.id.url = Some(Url::new_git("https://gitlab.com/alice/crev-proofs"));
+    let new = review_proof(&a, "bar", "1.0.0", good_review(), |_| {});
+    let crevette = crevette_for(&a, vec![old, new]);
+
+    assert_eq!(
+        "\"alice\" (https://gitlab.com/alice)",
+        who_string(&only_entry(&crevette, "foo"))
+    );
+    assert_eq!(
+        who_string(&only_entry(&crevette, "foo")),
+        who_string(&only_entry(&crevette, "bar"))
+    );
+}
+
+#[test]
+fn undefined_criteria_are_listed() {
+    let crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    let mut doc = crevette.convert_to_document().unwrap();
+    assert_eq!(Ok(()), doc.validate_criteria());
+
+    doc.criteria.remove("level-high");
+    doc.criteria.remove("level-low");
+    let mut errors = doc.validate_criteria().unwrap_err();
+    errors.sort();
+    // level-low is implied by level-medium, and level-high is used by the audit
+    assert_eq!(
+        vec!["level-high".to_string(), "level-low".to_string()],
+        errors
+    );
+}
+
+#[test]
+fn delta_reads_from_diff_base() {
+    let a = alice();
+    let crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", good_review(), |p| {
+                p.diff_base = Some(package_info("foo", "1.1.0"))
+            }),
+            review_proof(&a, "bar", "1.0.0", good_review(), |p| {
+                p.diff_base = Some(package_info("bar", "0.9.0"))
+            }),
+            review_proof(&a, "baz", "1.0.0", good_review(), |p| {
+                p.diff_base = Some(package_info("baz", "1.0.0"))
+            }),
+        ],
+    );
+    assert_eq!(
+        Some("1.1.0 -> 1.0.0"),
+        only_entry(&crevette, "foo").delta.as_deref()
+    );
+    assert_eq!(
+        Some("0.9.0 -> 1.0.0"),
+        only_entry(&crevette, "bar").delta.as_deref()
+    );
+    assert!(!crevette
+        .convert_to_document()
+        .unwrap()
+        .audits
+        .contains_key("baz"));
+}
+
+#[test]
+fn strict_mode_fails_on_dropped_reviews() {
+    let a = alice();
+    let worse = Review {
+        thoroughness: Level::Medium,
+        understanding: Level::Medium,
+        rating: Rating::Positive,
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.1.0", good_review(), |_| {}),
+            review_proof(&a, "foo", "1.0.0", worse, |_| {}),
+        ],
+    );
+    crevette.set_strict(true);
+    match crevette.convert_to_document() {
+        Err(Error::QualifiedReviewsDropped(dropped)) => {
+            assert_eq!(1, dropped.len());
+            assert!(dropped[0].starts_with("foo 1.0.0 by "));
+        }
+        _ => panic!("expected dropped reviews"),
+    }
+
+    crevette.set_emit_all_reviews(true);
+    assert_eq!(
+        2,
+        crevette.convert_to_document().unwrap().audits["foo"].len()
+    );
+}
+
+#[cfg(feature = "conda")]
+#[test]
+fn condaforge_recipes() {
+    let meta_yaml = r#"{% set name = "foo-cli" %}
+{% set version = "1.2.3" %}
+
+package:
+  name: {{ name|lower }}
+  version: {{ version }}
+
+source:
+  - url: https://crates.io/api/v1/crates/{{ name }}/{{ version }}/download
+    sha256: 0000
+  - git_url: https://github.com/foo/foo-cli
+"#;
+    assert_eq!(
+        vec![("foo-cli".to_string(), "1.2.3".to_string())],
+        conda::crates_in_recipe(meta_yaml)
+    );
+
+    let recipe_yaml = r#"context:
+  version: "0.4.0"
+
+source:
+  url: https://static.crates.io/crates/bar/bar-${{ version }}.crate
+"#;
+    assert_eq!(
+        vec![("bar".to_string(), "0.4.0".to_string())],
+        conda::crates_in_recipe(recipe_yaml)
+    );
+
+    let github = "{% set version = \"2.0.0\" %}\nsource:\n  url: https://github.com/foo/baz/archive/{{ version }}.tar.gz\n";
+    assert!(conda::crates_in_recipe(github).is_empty());
+
+    let dir = TempDir::new("conda");
+    let recipe_dir = conda::feedstock_dir(dir.path(), "foo-cli").join("recipe");
+    std::fs::create_dir_all(&recipe_dir).unwrap();
+    std::fs::write(recipe_dir.join("meta.yaml"), meta_yaml).unwrap();
+    let toml = Crevette::from_condaforge_repo(dir.path(), &["foo-cli"], true, None);
+    let toml = toml.unwrap();
+    assert!(toml.contains("[[audits.foo-cli]]"));
+    assert!(toml.contains("https://github.com/conda-forge/foo-cli-feedstock"));
+    assert!(toml.contains("Packaged for conda-forge (foo-cli-feedstock)"));
+}
+
+#[test]
+fn policy_suggested_for_strongly_audited_crates() {
+    let a = alice();
+    let strong = Review {
+        rating: Rating::Strong,
+        ..good_review()
+    };
+    let mut crevette = crevette_for(
+        &a,
+        vec![
+            review_proof(&a, "foo", "1.0.0", strong, |_| {}),
+            review_proof(&a, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    assert!(crevette.convert_to_config_document().policy.is_empty());
+
+    crevette.set_suggest_policy(true);
+    let config = crevette.convert_to_config_document();
+    assert_eq!(vec!["foo"], config.policy.keys().collect::<Vec<_>>());
+    assert_eq!(vec!["safe-to-deploy"], config.policy["foo"].criteria);
+    assert!(crevette
+        .convert_to_config_toml()
+        .unwrap()
+        .contains("[policy.foo]"));
+}
+
+#[test]
+fn derived_crevettes_share_data() {
+    let a = alice();
+    let b = UnlockedId::generate_for_git_url("https://github.com/bob/crev-proofs");
+    let trust =
+        a.id.create_trust_proof([b.as_public_id()], TrustLevel::Low, vec![])
+            .unwrap()
+            .sign_by(&a)
+            .unwrap();
+    let lenient = crevette_for(
+        &a,
+        vec![
+            trust,
+            review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+            review_proof(&b, "bar", "1.0.0", good_review(), |_| {}),
+        ],
+    );
+    let strict = lenient.with_min_trust_level(TrustLevel::High);
+    assert!(Arc::ptr_eq(&lenient.db, &strict.db));
+
+    let lenient = lenient.convert_to_document().unwrap();
+    let strict = strict.convert_to_document().unwrap();
+    assert!(lenient.audits.contains_key("bar"));
+    assert!(!strict.audits.contains_key("bar"));
+    assert!(strict.audits.contains_key("foo"));
+}
+
+#[test]
+fn issue_only_reviews_are_violations() {
+    let a = alice();
+    let issue = |severity| {
+        move |r: &mut Package| {
+            let mut issue = crev_data::review::Issue::new("panics-on-empty-input".into());
+            issue.severity = severity;
+            r.issues.push(issue);
+        }
+    };
+    let unrated = review_proof(&a, "foo", "1.0.0", Review::new_none(), issue(Level::High));
+    let neutral = review_proof(
+        &a,
+        "bar",
+        "1.0.0",
+        Review {
+            rating: Rating::Neutral,
+            ..good_review()
+        },
+        issue(Level::Low),
+    );
+    let mut crevette = crevette_for(&a, vec![unrated, neutral]);
+    crevette.set_include_neutral_reviews(false);
+
+    let foo = only_entry(&crevette, "foo");
+    assert_eq!(Some("=1.0.0"), foo.violation.as_deref());
+    assert_eq!(vec!["safe-to-deploy", "safe-to-run"], foo.criteria);
+    let bar = only_entry(&crevette, "bar");
+    assert_eq!(Some("=1.0.0"), bar.violation.as_deref());
+    assert_eq!(vec!["level-low"], bar.criteria);
+}
+
+#[test]
+fn estimated_entry_count_matches_document() {
+    let a = alice();
+    let weak = Review {
+        thoroughness: Level::None,
+        understanding: Level::Low,
+        rating: Rating::Positive,
+    };
+    let proofs = vec![
+        review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+        review_proof(&a, "foo", "1.1.0", good_review(), |r| {
+            r.flags.unmaintained = true
+        }),
+        review_proof(&a, "bar", "1.0.0", Review::new_negative(), |_| {}),
+        review_proof(&a, "baz", "1.0.0", weak, |_| {}),
+    ];
+    let mut crevette = crevette_for(&a, proofs);
+    crevette.set_unmaintained_as_violation(true);
+    let count = |crevette: &Crevette| {
+        crevette
+            .convert_to_document()
+            .unwrap()
+            .audits
+            .values()
+            .map(Vec::len)
+            .sum::<usize>()
+    };
+    assert_eq!(count(&crevette), crevette.estimate_entry_count());
+
+    crevette.set_allowed_criteria(Some(["safe-to-deploy".to_string()].into_iter().collect()));
+    assert_eq!(count(&crevette), crevette.estimate_entry_count());
+}
+
+#[test]
+fn who_as_array_for_single_reviewer() {
+    let mut crevette = alice_crevette("foo", "1.0.0", good_review(), |_| {});
+    assert!(matches!(
+        only_entry(&crevette, "foo").who,
+        vet::StringOrVec::String(_)
+    ));
+
+    crevette.set_who_always_vec(true);
+    let entry = only_entry(&crevette, "foo");
+    assert!(matches!(&entry.who, vet::StringOrVec::Vec(who) if who.len() == 1));
+    let toml = crevette.convert_to_toml().unwrap();
+    assert!(toml.contains("who = ["), "{toml}");
+}
+
+#[test]
+fn crate_globs_select_crate_families() {
+    let a = alice();
+    let proofs = ["serde", "serde_json", "tokio", "my-serde"]
+        .iter()
+        .map(|name| review_proof(&a, name, "1.0.0", good_review(), |_| {}))
+        .collect();
+    let mut crevette = crevette_for(&a, proofs);
+    crevette.set_crate_glob(vec![]);
+    assert_eq!(4, crevette.convert_to_document().unwrap().audits.len());
+
+    crevette.set_crate_glob(vec!["serde*".into()]);
+    let doc = crevette.convert_to_document().unwrap();
+    assert_eq!(
+        ["serde", "serde_json"],
+        doc.audits.keys().map(String::as_str).collect::<Vec<_>>()[..]
+    );
+
+    assert!(glob_matches("tokio-*", "tokio-util"));
+    assert!(glob_matches("*-s?s", "tokio-sys"));
+    assert!(glob_matches("a*b*c", "aXbYbZc"));
+    assert!(!glob_matches("tokio-*", "tokio"));
+    assert!(!glob_matches("serde", "serde_json"));
+}
+
+#[test]
+fn severity_summary_in_violation_notes() {
+    let mut crevette = alice_crevette("foo", "1.0.0", Review::new_negative(), |r| {
+        for (id, severity) in [
+            ("RUSTSEC-2021-0001", Level::High),
+            ("RUSTSEC-2021-0002", Level::Medium),
+            ("RUSTSEC-2021-0003", Level::High),
+        ] {
+            r.advisories.push(crev_data::review::Advisory {
+                ids: vec![id.into()],
+                severity,
+                ..Default::default()
+            });
+        }
+    });
+    assert!(!only_entry(&crevette, "foo")
+        .notes
+        .unwrap()
+        .contains("advisories:"));
+
+    crevette.set_severity_summary(true);
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert!(notes.contains("3 advisories: 2 high, 1 medium"), "{notes}");
+
+    crevette.set_advisory_severity_names(true);
+    let notes = only_entry(&crevette, "foo").notes.unwrap();
+    assert!(
+        notes.contains("3 advisories: 2 critical, 1 moderate"),
+        "{notes}"
+    );
+}
+
+#[test]
+#[cfg(feature = "debcargo")]
+fn stale_cache_is_removed() {
+    let dir = TempDir::new("cache");
+    let cached = dir.path().join("Sources.gz");
+    std::fs::write(&cached, b"old").unwrap();
+    let day = std::time::Duration::from_secs(24 * 60 * 60);
+    let week_later = std::time::SystemTime::now() + 7 * day;
+
+    remove_stale_cache(&cached, None, week_later).unwrap();
+    remove_stale_cache(&cached, Some(30 * day), week_later).unwrap();
+    assert!(cached.exists());
+
+    remove_stale_cache(&cached, Some(day), week_later).unwrap();
+    assert!(!cached.exists());
+    remove_stale_cache(&cached, Some(day), week_later).unwrap();
+}
+
+#[test]
+#[cfg(feature = "debcargo")]
+fn debcargo_offline_needs_cached_sources() {
+    let dir = TempDir::new("offline-deb");
+    let mut opts = DebianOptions {
+        offline: true,
+        ..Default::default()
+    };
+    let stable = Crevette::from_debcargo_repo(dir.path(), &opts, None);
+    opts.suite = "sid".into();
+    let sid = Crevette::from_debcargo_repo(dir.path(), &opts, None);
+    let err = stable.unwrap_err().to_string();
+    assert!(err.contains("Sources.gz"), "{err}");
+    let err = sid.unwrap_err().to_string();
+    assert!(err.contains("Sources-sid.gz"), "{err}");
+}
+
+#[test]
+#[cfg(feature = "opensuse")]
+fn opensuse_offline_needs_cached_primary() {
+    let dir = TempDir::new("offline-suse");
+    let opts = OpensuseOptions {
+        offline: true,
+        ..Default::default()
+    };
+    let res = Crevette::from_opensuse_repo(dir.path(), &opts, None);
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("opensuse-primary.xml.gz"), "{err}");
+}
+
+#[test]
+#[cfg(feature = "guix")]
+fn guix_offline_needs_checkout() {
+    let dir = TempDir::new("offline-guix");
+    let res = Crevette::from_guix_repo(dir.path(), None, true, None);
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("offline mode"), "{err}");
+}
+
+#[test]
+fn report_matches_toml() {
+    let a = alice();
+    let proofs = vec![
+        review_proof(&a, "foo", "1.0.0", good_review(), |_| {}),
+        review_proof(&a, "foo", "1.1.0", good_review(), |_| {}),
+        review_proof(&a, "bar", "1.0.0", Review::new_negative(), |_| {}),
+    ];
+    let mut crevette = crevette_for(&a, proofs);
+    crevette.set_emit_all_reviews(true);
+    let (toml, report) = crevette.convert_with_report().unwrap();
+    assert_eq!(crevette.convert_to_toml().unwrap(), toml);
+
+    let parsed: BTreeMap<String, serde_json::Value> = toml_edit::de::from_str(&toml).unwrap();
+    let toml_entries: usize = parsed["audits"]
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|entries| entries.as_array().unwrap().len())
+        .sum();
+    assert_eq!(3, toml_entries);
+    assert_eq!(toml_entries, report.entry_count);
+    assert_eq!(Some(&2), report.entries_per_crate.get("foo"));
+    assert_eq!(1, report.reviewers.len());
+    assert_eq!(sha256_hex(toml.as_bytes()), report.sha256);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(3, json["entry_count"]);
+}
@@ -1,14 +1,16 @@
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum StringOrVec {
     String(String),
     Vec(Vec<String>),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub struct AuditEntry {
     pub who: StringOrVec,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -26,9 +28,9 @@ pub struct AuditEntry {
     pub aggregated_from: Vec<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub struct CriteriaEntry {
-    pub description: Option<&'static str>,
+    pub description: Option<Cow<'static, str>>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub implies: Vec<&'static str>,
     #[serde(rename = "aggregated-from")]
@@ -41,3 +43,264 @@ pub struct AuditsFile {
     pub audits: BTreeMap<String, Vec<AuditEntry>>,
     pub criteria: BTreeMap<&'static str, CriteriaEntry>,
 }
+
+/// Shape of `audits.toml`, for compatibility with older cargo-vet releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VetSchema {
+    /// For cargo-vet releases older than `cargo vet aggregate`, which don't know the `aggregated-from` field.
+    /// Provenance of audits and criteria is left out.
+    V1,
+    /// Current cargo-vet, with all fields crevette can emit
+    #[default]
+    V2,
+}
+
+/// Suggested parts of cargo-vet's `config.toml`
+#[derive(Serialize, Default)]
+pub struct ConfigFile {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub policy: BTreeMap<String, PolicyEntry>,
+}
+
+/// A `[policy.CRATE]` table
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PolicyEntry {
+    pub criteria: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// Publisher trusted to release versions of a crate, from `[[trusted.CRATE]]`
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct TrustEntry {
+    pub criteria: Vec<&'static str>,
+    /// crates.io user id
+    pub user_id: u64,
+    /// `YYYY-MM-DD`
+    pub start: String,
+    /// `YYYY-MM-DD`
+    pub end: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// The `trusted` table of cargo-vet's `audits.toml`
+#[derive(Serialize)]
+pub struct TrustedFile {
+    pub trusted: BTreeMap<String, Vec<TrustEntry>>,
+}
+
+/// Differences between two `AuditsFile`s, from `AuditsFile::diff`
+#[derive(Debug, Default)]
+pub struct AuditsDiff {
+    /// Audits only in the other file, per crate
+    pub added: BTreeMap<String, Vec<AuditEntry>>,
+    /// Audits only in this file, per crate
+    pub removed: BTreeMap<String, Vec<AuditEntry>>,
+    /// `(old, new)` audits of the same version by the same reviewer, with different criteria or notes
+    pub changed: BTreeMap<String, Vec<(AuditEntry, AuditEntry)>>,
+    pub added_criteria: Vec<&'static str>,
+    pub removed_criteria: Vec<&'static str>,
+    /// Criteria with a different description or implied criteria
+    pub changed_criteria: Vec<&'static str>,
+}
+
+impl AuditsDiff {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.added_criteria.is_empty()
+            && self.removed_criteria.is_empty()
+            && self.changed_criteria.is_empty()
+    }
+}
+
+impl StringOrVec {
+    /// Either one or all of the strings
+    #[must_use]
+    pub fn as_vec(&self) -> Vec<&str> {
+        match self {
+            Self::String(s) => vec![s.as_str()],
+            Self::Vec(v) => v.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl AuditEntry {
+    /// Identifies the same audit across files, even if its criteria or notes have changed
+    fn diff_key(&self) -> (Option<&str>, Option<&str>, Option<&str>, Vec<&str>) {
+        (
+            self.violation.as_deref(),
+            self.version.as_deref(),
+            self.delta.as_deref(),
+            self.who.as_vec(),
+        )
+    }
+}
+
+/// Criteria that cargo-vet defines itself
+pub const BUILT_IN_CRITERIA: [&str; 2] = ["safe-to-run", "safe-to-deploy"];
+
+impl AuditsFile {
+    /// Removes fields that the given `schema` doesn't have
+    pub fn downgrade_to(&mut self, schema: VetSchema) {
+        if schema == VetSchema::V1 {
+            for entry in self.audits.values_mut().flatten() {
+                entry.aggregated_from.clear();
+            }
+            for c in self.criteria.values_mut() {
+                c.aggregated_from.clear();
+            }
+        }
+    }
+
+    /// What has to change to get from this file to the `other` one.
+    ///
+    /// Audits are matched by crate, version or delta, and reviewer, so their order doesn't matter.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> AuditsDiff {
+        let mut diff = AuditsDiff::default();
+
+        let no_entries = Vec::new();
+        for crate_name in self
+            .audits
+            .keys()
+            .chain(other.audits.keys())
+            .collect::<BTreeSet<_>>()
+        {
+            let old = self.audits.get(crate_name).unwrap_or(&no_entries);
+            let new = other.audits.get(crate_name).unwrap_or(&no_entries);
+
+            let mut unmatched: Vec<_> = new.iter().collect();
+            for old_entry in old {
+                let key = old_entry.diff_key();
+                match unmatched.iter().position(|n| n.diff_key() == key) {
+                    Some(pos) => {
+                        let new_entry = unmatched.remove(pos);
+                        if new_entry != old_entry {
+                            diff.changed
+                                .entry(crate_name.clone())
+                                .or_insert_with(Vec::new)
+                                .push((old_entry.clone(), new_entry.clone()));
+                        }
+                    }
+                    None => diff
+                        .removed
+                        .entry(crate_name.clone())
+                        .or_insert_with(Vec::new)
+                        .push(old_entry.clone()),
+                }
+            }
+            if !unmatched.is_empty() {
+                diff.added
+                    .insert(crate_name.clone(), unmatched.into_iter().cloned().collect());
+            }
+        }
+
+        for (name, old) in &self.criteria {
+            match other.criteria.get(name) {
+                Some(new) if new != old => diff.changed_criteria.push(*name),
+                Some(_) => {}
+                None => diff.removed_criteria.push(*name),
+            }
+        }
+        diff.added_criteria = other
+            .criteria
+            .keys()
+            .filter(|name| !self.criteria.contains_key(*name))
+            .copied()
+            .collect();
+        diff
+    }
+
+    /// Names of all criteria that audits or `implies` refer to, but which aren't cargo-vet's built-ins nor defined in `criteria`.
+    ///
+    /// Unlike `validate`, it doesn't stop at the first one.
+    pub fn validate_criteria(&self) -> Result<(), Vec<String>> {
+        let is_defined = |c: &str| BUILT_IN_CRITERIA.contains(&c) || self.criteria.contains_key(c);
+
+        let undefined: BTreeSet<_> = self
+            .criteria
+            .values()
+            .flat_map(|c| &c.implies)
+            .chain(
+                self.audits
+                    .values()
+                    .flatten()
+                    .flat_map(|entry| &entry.criteria),
+            )
+            .filter(|&&c| !is_defined(c))
+            .collect();
+        if undefined.is_empty() {
+            return Ok(());
+        }
+        Err(undefined.into_iter().map(|c| c.to_string()).collect())
+    }
+
+    /// Checks rules that cargo-vet enforces when loading audits
+    pub fn validate(&self) -> Result<(), InvalidAudit> {
+        let is_defined = |c: &str| BUILT_IN_CRITERIA.contains(&c) || self.criteria.contains_key(c);
+
+        for (name, c) in &self.criteria {
+            if let Some(undefined) = c.implies.iter().find(|&&c| !is_defined(c)) {
+                return Err(InvalidAudit {
+                    crate_name: None,
+                    reason: format!("criterion '{name}' implies undefined criterion '{undefined}'"),
+                });
+            }
+        }
+
+        for (crate_name, entries) in &self.audits {
+            for entry in entries {
+                let invalid = |reason: String| InvalidAudit {
+                    crate_name: Some(crate_name.clone()),
+                    reason,
+                };
+                if entry.violation.is_some() {
+                    if entry.version.is_some() || entry.delta.is_some() {
+                        return Err(invalid("violation can't have a version or delta".into()));
+                    }
+                } else {
+                    if entry.version.is_some() == entry.delta.is_some() {
+                        return Err(invalid(
+                            "audit must have exactly one of version or delta".into(),
+                        ));
+                    }
+                    let has_who = match &entry.who {
+                        StringOrVec::String(who) => !who.is_empty(),
+                        StringOrVec::Vec(who) => !who.is_empty(),
+                    };
+                    if !has_who {
+                        return Err(invalid("audit has no `who`".into()));
+                    }
+                }
+                if let Some(undefined) = entry.criteria.iter().find(|&&c| !is_defined(c)) {
+                    return Err(invalid(format!("undefined criterion '{undefined}'")));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Audits file that cargo-vet would reject
+#[derive(Debug)]
+pub struct InvalidAudit {
+    /// `None` if the problem is in the criteria definitions
+    pub crate_name: Option<String>,
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.crate_name {
+            Some(crate_name) => write!(f, "Invalid audit of {crate_name}: {}", self.reason),
+            None => write!(f, "Invalid audits file: {}", self.reason),
+        }
+    }
+}
+
+impl std::error::Error for InvalidAudit {}